@@ -12,16 +12,23 @@ pub fn generate_ast(output_dir: &String) -> io::Result<()> {
     define_ast(
         output_dir,
         &"Expr".to_string(),
-        &["error", "token"],
+        &["error", "token", "stmt"],
         &[
             "Assign     : Token name, Rc<Expr> value",
             "Binary     : Rc<Expr> left, Token operator, Rc<Expr> right",
             "Call       : Rc<Expr> callee, Token paren, Vec<Rc<Expr>> arguments",
             "Get        : Rc<Expr> object, Token name",
             "Grouping   : Rc<Expr> expression",
+            "Index      : Rc<Expr> object, Token bracket, Rc<Expr> index",
+            "IndexSet   : Rc<Expr> object, Token bracket, Rc<Expr> index, Rc<Expr> value",
+            "Interpolation : Token token, Vec<Rc<Expr>> parts",
+            "Lambda     : Token keyword, Rc<Vec<Token>> params, Rc<Vec<Rc<Stmt>>> body",
+            "List       : Token bracket, Vec<Rc<Expr>> elements",
             "Literal    : Option<Object> value",
+            "Map        : Token brace, Vec<Rc<Expr>> keys, Vec<Rc<Expr>> values",
             "Logical    : Rc<Expr> left, Token operator, Rc<Expr> right",
             "Set        : Rc<Expr> object, Token name, Rc<Expr> value",
+            "Slice      : Rc<Expr> object, Token bracket, Option<Rc<Expr>> start, Option<Rc<Expr>> end",
             "Super      : Token keyword, Token method",
             "This       : Token keyword",
             "Unary      : Token operator, Rc<Expr> right",
@@ -37,13 +44,14 @@ pub fn generate_ast(output_dir: &String) -> io::Result<()> {
             "Break      : Token token",
             "Block      : Rc<Vec<Rc<Stmt>>> statements",
             "Class      : Token name, Option<Rc<Expr>> superclass, Rc<Vec<Rc<Stmt>>> methods",
+            "Continue   : Token token",
             "Expression : Rc<Expr> expression",
             "Function   : Token name, Rc<Vec<Token>> params, Rc<Vec<Rc<Stmt>>> body",
             "If         : Rc<Expr> condition, Rc<Stmt> then_branch, Option<Rc<Stmt>> else_branch",
-            "Print      : Rc<Expr> expression",
+            "Print      : Token keyword, Rc<Expr> expression",
             "Return     : Token keyword, Option<Rc<Expr>> value",
             "Var        : Token name, Option<Rc<Expr>> initializer",
-            "While      : Rc<Expr> condition, Rc<Stmt> body",
+            "While      : Rc<Expr> condition, Rc<Stmt> body, Option<Rc<Expr>> increment",
         ],
     )?;
 
@@ -129,12 +137,20 @@ fn define_ast(
             "            {0}::{1}(v) => {3}_visitor.visit_{2}_{3}(wrapper, v),",
             base_name,
             t.base_class_name,
-            t.base_class_name.to_lowercase(),
+            to_snake_case(&t.base_class_name),
             base_name.to_lowercase()
         )?;
     }
     writeln!(file, "        }}")?;
     writeln!(file, "    }}")?;
+    writeln!(file)?;
+    writeln!(file, "    pub fn span(&self) -> Span {{")?;
+    writeln!(file, "        match self {{")?;
+    for t in &tree_types {
+        writeln!(file, "            {0}::{1}(v) => v.span,", base_name, t.base_class_name)?;
+    }
+    writeln!(file, "        }}")?;
+    writeln!(file, "    }}")?;
     writeln!(file, "}}\n")?;
 
     // create Expr structs
@@ -143,6 +159,7 @@ fn define_ast(
         for f in &t.fields {
             writeln!(file, "    pub {},", f)?;
         }
+        writeln!(file, "    pub span: Span,")?;
         writeln!(file, "}}\n")?;
     }
 
@@ -152,7 +169,7 @@ fn define_ast(
         writeln!(
             file,
             "    fn visit_{0}_{1}(&self, wrapper: Rc<{3}>, {1}: &{2}) -> Result<T, LoxResult>;",
-            t.base_class_name.to_lowercase(),
+            to_snake_case(&t.base_class_name),
             base_name.to_lowercase(),
             t.class_name,
             base_name
@@ -181,3 +198,15 @@ fn define_ast(
 
     Ok(())
 }
+
+fn to_snake_case(name: &str) -> String {
+    // IndexSet -> index_set, for visitor method names
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
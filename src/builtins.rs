@@ -0,0 +1,196 @@
+use crate::error::*;
+use crate::interpreter::*;
+use crate::lox_map::*;
+use crate::token::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub fn define_builtins(interpreter: &Interpreter) {
+    interpreter.define_builtin("len", 1, |args| match &args[0] {
+        Object::List(list) => Ok(Object::Num(list.borrow().len() as f64)),
+        Object::Map(map) => Ok(Object::Num(map.borrow().len() as f64)),
+        Object::Str(s) => Ok(Object::Num(s.chars().count() as f64)),
+        _ => Err(ErrorMessage::new("L0316", "Argument must be a list, map or string.")),
+    });
+
+    // list natives; lists are shared, so these modify the caller's list in place
+    interpreter.define_builtin("push", 2, |args| {
+        let list = as_list(&args[0])?;
+        list.borrow_mut().push(args[1].clone());
+        Ok(Object::Nil)
+    });
+
+    interpreter.define_builtin("pop", 1, |args| {
+        let list = as_list(&args[0])?;
+        let value = list.borrow_mut().pop();
+        value.ok_or_else(|| ErrorMessage::new("L0313", "Can't pop from an empty list."))
+    });
+
+    interpreter.define_builtin("insert", 3, |args| {
+        let list = as_list(&args[0])?;
+        let len = list.borrow().len();
+        let index = checked_index(&args[1], len + 1)?;
+        list.borrow_mut().insert(index, args[2].clone());
+        Ok(Object::Nil)
+    });
+
+    interpreter.define_builtin("remove", 2, |args| {
+        let list = as_list(&args[0])?;
+        let len = list.borrow().len();
+        let index = checked_index(&args[1], len)?;
+        let value = list.borrow_mut().remove(index);
+        Ok(value)
+    });
+
+    interpreter.define_builtin("slice", 3, |args| slice_value(&args[0], Some(&args[1]), Some(&args[2])));
+
+    // map natives; keys and values come back in insertion order
+    interpreter.define_builtin("keys", 1, |args| {
+        let map = as_map(&args[0])?;
+        let keys = map.borrow().entries().iter().map(|(k, _)| k.clone()).collect();
+        Ok(Object::list(keys))
+    });
+
+    interpreter.define_builtin("values", 1, |args| {
+        let map = as_map(&args[0])?;
+        let values = map.borrow().entries().iter().map(|(_, v)| v.clone()).collect();
+        Ok(Object::list(values))
+    });
+
+    interpreter.define_builtin("has", 2, |args| {
+        let map = as_map(&args[0])?;
+        let found = map.borrow().contains(&args[1])?;
+        Ok(Object::Bool(found))
+    });
+
+    interpreter.define_builtin("delete", 2, |args| {
+        let map = as_map(&args[0])?;
+        let removed = map.borrow_mut().remove(&args[1])?;
+        Ok(Object::Bool(removed.is_some()))
+    });
+
+    // string natives; strings are immutable, so these all return new values
+    interpreter.define_builtin("upper", 1, |args| Ok(Object::Str(as_str(&args[0])?.to_uppercase())));
+
+    interpreter.define_builtin("lower", 1, |args| Ok(Object::Str(as_str(&args[0])?.to_lowercase())));
+
+    interpreter.define_builtin("trim", 1, |args| Ok(Object::Str(as_str(&args[0])?.trim().to_string())));
+
+    interpreter.define_builtin("split", 2, |args| {
+        let s = as_str(&args[0])?;
+        let separator = as_non_empty_str(&args[1])?;
+        let parts = s.split(separator).map(|part| Object::Str(part.to_string())).collect();
+        Ok(Object::list(parts))
+    });
+
+    interpreter.define_builtin("join", 2, |args| {
+        let list = as_list(&args[0])?;
+        let separator = as_str(&args[1])?;
+        let parts: Vec<String> = list.borrow().iter().map(|element| element.to_string()).collect();
+        Ok(Object::Str(parts.join(separator)))
+    });
+
+    interpreter.define_builtin("find", 2, |args| {
+        // the character index of the first match, or nil
+        let s = as_str(&args[0])?;
+        let needle = as_str(&args[1])?;
+        Ok(match s.find(needle) {
+            Some(byte) => Object::Num(s[..byte].chars().count() as f64),
+            None => Object::Nil,
+        })
+    });
+
+    interpreter.define_builtin("replace", 3, |args| {
+        let s = as_str(&args[0])?;
+        let from = as_non_empty_str(&args[1])?;
+        let to = as_str(&args[2])?;
+        Ok(Object::Str(s.replace(from, to)))
+    });
+
+    interpreter.define_builtin("startsWith", 2, |args| {
+        Ok(Object::Bool(as_str(&args[0])?.starts_with(as_str(&args[1])?)))
+    });
+
+    interpreter.define_builtin("endsWith", 2, |args| {
+        Ok(Object::Bool(as_str(&args[0])?.ends_with(as_str(&args[1])?)))
+    });
+
+    interpreter.define_builtin("chars", 1, |args| {
+        let chars = as_str(&args[0])?.chars().map(|c| Object::Str(c.to_string())).collect();
+        Ok(Object::list(chars))
+    });
+}
+
+fn as_list(value: &Object) -> Result<&Rc<RefCell<Vec<Object>>>, ErrorMessage> {
+    match value {
+        Object::List(list) => Ok(list),
+        _ => Err(ErrorMessage::new("L0316", "Argument must be a list.")),
+    }
+}
+
+fn as_map(value: &Object) -> Result<&Rc<RefCell<LoxMap>>, ErrorMessage> {
+    match value {
+        Object::Map(map) => Ok(map),
+        _ => Err(ErrorMessage::new("L0316", "Argument must be a map.")),
+    }
+}
+
+fn as_str(value: &Object) -> Result<&str, ErrorMessage> {
+    match value {
+        Object::Str(s) => Ok(s),
+        _ => Err(ErrorMessage::new("L0316", "Argument must be a string.")),
+    }
+}
+
+fn as_non_empty_str(value: &Object) -> Result<&str, ErrorMessage> {
+    match value {
+        Object::Str(s) if !s.is_empty() => Ok(s),
+        _ => Err(ErrorMessage::new("L0316", "Argument must be a non-empty string.")),
+    }
+}
+
+pub fn checked_index(index: &Object, len: usize) -> Result<usize, ErrorMessage> {
+    // indexes are whole numbers in 0..len
+    match index {
+        Object::Num(n) if n.fract() == 0.0 => {
+            if *n >= 0.0 && (*n as usize) < len {
+                Ok(*n as usize)
+            } else {
+                Err(ErrorMessage::new("L0313", "Index out of bounds."))
+            }
+        }
+        _ => Err(ErrorMessage::new("L0314", "Index must be a whole number.")),
+    }
+}
+
+fn slice_range(start: Option<&Object>, end: Option<&Object>, len: usize) -> Result<(usize, usize), ErrorMessage> {
+    // a missing bound means the start or end; both may equal len
+    let start = start.map_or(Ok(0), |start| checked_index(start, len + 1))?;
+    let end = end.map_or(Ok(len), |end| checked_index(end, len + 1))?;
+    if start > end {
+        return Err(ErrorMessage::new("L0313", "Index out of bounds."));
+    }
+    Ok((start, end))
+}
+
+pub fn char_at(s: &str, index: &Object) -> Result<Object, ErrorMessage> {
+    // strings are indexed by Unicode scalar value, not by byte
+    let i = checked_index(index, s.chars().count())?;
+    Ok(Object::Str(s.chars().skip(i).take(1).collect()))
+}
+
+pub fn slice_value(value: &Object, start: Option<&Object>, end: Option<&Object>) -> Result<Object, ErrorMessage> {
+    // shared by slice() and the value[start:end] syntax; always returns a copy
+    match value {
+        Object::List(list) => {
+            let list = list.borrow();
+            let (start, end) = slice_range(start, end, list.len())?;
+            Ok(Object::list(list[start..end].to_vec()))
+        }
+        Object::Str(s) => {
+            let (start, end) = slice_range(start, end, s.chars().count())?;
+            Ok(Object::Str(s.chars().skip(start).take(end - start).collect()))
+        }
+        _ => Err(ErrorMessage::new("L0315", "Only lists and strings can be sliced.")),
+    }
+}
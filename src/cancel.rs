@@ -0,0 +1,71 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle {
+    flag: Arc<AtomicBool>,
+}
+
+impl CancelHandle {
+    pub fn new() -> CancelHandle {
+        CancelHandle {
+            flag: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn cancel(&self) {
+        // safe to call from any thread; the interpreter stops at its next step.
+        // A cancel made before a run starts stops that run as soon as it does,
+        // and the flag is cleared once a run has stopped because of it
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+    }
+
+    pub fn reset(&self) {
+        self.flag.store(false, Ordering::Relaxed);
+    }
+}
+
+#[cfg(unix)]
+pub mod interrupt {
+    use super::CancelHandle;
+    use std::sync::OnceLock;
+
+    const SIGINT: i32 = 2;
+
+    static HANDLE: OnceLock<CancelHandle> = OnceLock::new();
+
+    extern "C" {
+        fn signal(signum: i32, handler: usize) -> usize;
+    }
+
+    const SIG_DFL: usize = 0;
+
+    extern "C" fn on_interrupt(_: i32) {
+        if let Some(handle) = HANDLE.get() {
+            handle.cancel();
+        }
+    }
+
+    pub fn handle() -> CancelHandle {
+        // process-wide handle cancelled by Ctrl-C while inside catch()
+        HANDLE.get_or_init(CancelHandle::new).clone()
+    }
+
+    pub fn catch<T>(f: impl FnOnce() -> T) -> T {
+        // Ctrl-C cancels the handle instead of killing the process only while
+        // f runs, so outside of it the default behaviour is kept
+        handle();
+        unsafe {
+            signal(SIGINT, on_interrupt as extern "C" fn(i32) as usize);
+        }
+        let result = f();
+        unsafe {
+            signal(SIGINT, SIG_DFL);
+        }
+        result
+    }
+}
@@ -0,0 +1,223 @@
+use crate::error::*;
+use crate::token::*;
+use crate::token_type::*;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum DiagnosticKind {
+    Lexical,
+    Syntax,
+    Resolution,
+    Runtime,
+    System,
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let category = match self {
+            DiagnosticKind::Lexical => "lexical",
+            DiagnosticKind::Syntax => "syntax",
+            DiagnosticKind::Resolution => "resolution",
+            DiagnosticKind::Runtime => "runtime",
+            DiagnosticKind::System => "system",
+        };
+        write!(f, "{category}")
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackFrame {
+    pub function: String,
+    pub line: usize,
+}
+
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "at {} (line {})", self.function, self.line)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub code: Option<&'static str>,
+    pub severity: Severity,
+    pub message: String,
+    pub line: usize,
+    pub span: Option<Span>,
+    pub token: Option<Token>,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub trace: Vec<StackFrame>,
+}
+
+impl Diagnostic {
+    pub fn new(
+        kind: DiagnosticKind,
+        severity: Severity,
+        code: Option<&'static str>,
+        message: &str,
+        line: usize,
+        token: Option<&Token>,
+    ) -> Diagnostic {
+        Diagnostic {
+            kind,
+            code,
+            severity,
+            message: message.to_string(),
+            line,
+            span: token.map(|t| t.span),
+            token: token.map(|t| t.duplicate()),
+            labels: Vec::new(),
+            notes: Vec::new(),
+            trace: Vec::new(),
+        }
+    }
+
+    pub fn at(kind: DiagnosticKind, severity: Severity, code: Option<&'static str>, message: &str, token: &Token) -> Diagnostic {
+        Diagnostic::new(kind, severity, code, message, token.line, Some(token))
+    }
+
+    pub fn with_span(mut self, span: Span) -> Diagnostic {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_label(mut self, span: Span, message: &str) -> Diagnostic {
+        // secondary location shown alongside the primary span
+        self.labels.push(Label {
+            span,
+            message: message.to_string(),
+        });
+        self
+    }
+
+    pub fn with_help(mut self, note: &str) -> Diagnostic {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    pub fn with_trace(mut self, trace: &[StackFrame]) -> Diagnostic {
+        self.trace = trace.to_vec();
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // one line without the source, for hosts that log diagnostics; rlox
+        // itself renders them with Renderer
+        match (&self.kind, &self.token) {
+            (DiagnosticKind::System, _) => write!(f, "System Error: {}", self.message),
+            (_, Some(token)) if token.is(TokenType::Eof) => {
+                write!(f, "[line {}] at end: {}", self.line, self.message)
+            }
+            (_, Some(token)) => {
+                write!(f, "[line {}] at '{}': {}", self.line, token.as_string(), self.message)
+            }
+            (_, None) if self.is_error() => write!(f, "[line {}] Error: {}", self.line, self.message),
+            (_, None) => write!(f, "[line {}] Warning: {}", self.line, self.message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    entries: Rc<RefCell<Vec<Diagnostic>>>,
+    // index of the first entry collected by the current run
+    run_start: Rc<RefCell<usize>>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Diagnostics {
+        Diagnostics {
+            entries: Rc::new(RefCell::new(Vec::new())),
+            run_start: Rc::new(RefCell::new(0)),
+        }
+    }
+
+    pub fn start_run(&self) {
+        // entries the host hasn't taken yet are kept, but only those pushed
+        // from here on are deduplicated and affected by the warning mode
+        self.run_start.replace(self.entries.borrow().len());
+    }
+
+    pub fn push(&self, diagnostic: Diagnostic) {
+        // a second error at the same place in a run is almost always a cascade
+        // of the first
+        let start = *self.run_start.borrow();
+        let duplicate = diagnostic.is_error()
+            && diagnostic.span.is_some()
+            && self.entries.borrow()[start..]
+                .iter()
+                .any(|d| d.is_error() && d.span == diagnostic.span);
+        if !duplicate {
+            self.entries.borrow_mut().push(diagnostic);
+        }
+    }
+
+    pub fn report(&self, error: &LoxResult) {
+        if let Some(diagnostic) = error.diagnostic() {
+            self.push(diagnostic);
+        }
+    }
+
+    pub fn has_warnings(&self) -> bool {
+        let start = *self.run_start.borrow();
+        self.entries.borrow()[start..].iter().any(|d| !d.is_error())
+    }
+
+    pub fn allow_warnings(&self) {
+        let start = *self.run_start.borrow();
+        let mut entries = self.entries.borrow_mut();
+        let run = entries.split_off(start);
+        entries.extend(run.into_iter().filter(|d| d.is_error()));
+    }
+
+    pub fn deny_warnings(&self) {
+        let start = *self.run_start.borrow();
+        for diagnostic in self.entries.borrow_mut()[start..].iter_mut() {
+            diagnostic.severity = Severity::Error;
+        }
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.entries.borrow().iter().any(|d| d.is_error())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+
+    pub fn entries(&self) -> Vec<Diagnostic> {
+        self.entries.borrow().clone()
+    }
+
+    pub fn take(&self) -> Vec<Diagnostic> {
+        self.run_start.replace(0);
+        self.entries.take()
+    }
+
+    pub fn clear(&self) {
+        self.run_start.replace(0);
+        self.entries.borrow_mut().clear();
+    }
+}
@@ -10,6 +10,12 @@ pub struct Environment {
     enclosing: Option<Rc<RefCell<Environment>>>,
 }
 
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Environment {
     pub fn new() -> Environment {
         Environment {
@@ -28,11 +34,11 @@ impl Environment {
         self.values.insert(name.to_string(), value);
     }
 
-    pub fn get_at(&self, distance: usize, name: &str) -> Result<Object, LoxResult> {
+    pub fn get_at(&self, distance: usize, name: &str) -> Option<Object> {
         if distance == 0 {
-            Ok(self.values.get(name).unwrap().clone())
+            self.values.get(name).cloned()
         } else {
-            self.enclosing.as_ref().unwrap().borrow().get_at(distance - 1, name)
+            self.enclosing.as_ref()?.borrow().get_at(distance - 1, name)
         }
     }
 
@@ -40,8 +46,14 @@ impl Environment {
         if distance == 0 {
             self.values.insert(name.as_string().into(), value);
             Ok(())
+        } else if let Some(enclosing) = &self.enclosing {
+            enclosing.borrow_mut().assign_at(distance - 1, name, value)
         } else {
-            self.enclosing.as_ref().unwrap().borrow_mut().assign_at(distance - 1, name, value)
+            Err(LoxResult::runtime_error(
+                name,
+                "L0303",
+                &format!("Undefined variable '{}'.", name.as_string()),
+            ))
         }
     }
 
@@ -53,6 +65,7 @@ impl Environment {
         } else {
             Err(LoxResult::runtime_error(
                 name,
+                "L0303",
                 &format!("Undefined variable '{}'.", name.as_string()),
             ))
         }
@@ -67,7 +80,8 @@ impl Environment {
         } else {
             Err(LoxResult::runtime_error(
                 name,
-                &format!("Underfined variable '{}'.", name.as_string()),
+                "L0303",
+                &format!("Undefined variable '{}'.", name.as_string()),
             ))
         }
     }
@@ -1,15 +1,48 @@
+use crate::diagnostic::*;
 use crate::token::*;
-use crate::token_type::*;
+use std::fmt;
 
 #[derive(Debug)]
 pub enum LoxResult {
-    LoxParseError { token: Token, message: String },
-    LoxRuntimeError { token: Token, message: String },
-    LoxError { line: usize, message: String },
-    LoxSystemError { message: String},
-    LoxResolverError { token: Token, message: String },
+    LoxParseError { token: Token, code: &'static str, message: String },
+    LoxRuntimeError { token: Token, code: Option<&'static str>, message: String, trace: Vec<StackFrame> },
+    LoxError { span: Span, code: &'static str, message: String },
+    LoxSystemError { code: &'static str, message: String },
+    LoxResolverError { token: Token, code: &'static str, message: String },
+    LoxFuelExhausted { span: Span },
+    LoxNestingError { kind: DiagnosticKind, span: Span },
+    Interrupted,
     Return { value: Object },
     Break,
+    Continue,
+}
+
+// A runtime error raised before there is a token to cite, such as by an
+// operator or a native function; the interpreter reports it at the call
+// or operator. Errors from host natives have no code.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorMessage {
+    pub code: Option<&'static str>,
+    pub message: String,
+}
+
+impl ErrorMessage {
+    pub fn new(code: &'static str, message: &str) -> ErrorMessage {
+        ErrorMessage {
+            code: Some(code),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn host(message: String) -> ErrorMessage {
+        ErrorMessage { code: None, message }
+    }
+}
+
+impl fmt::Display for ErrorMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
 }
 
 impl LoxResult {
@@ -19,78 +52,95 @@ impl LoxResult {
         }
     }
 
-    pub fn error(line: usize, message: &str) -> LoxResult {
+    pub fn error(span: Span, code: &'static str, message: &str) -> LoxResult {
         // scanning error; tokens don't exist at this point
-        let e = LoxResult::LoxError {
-            line,
+        LoxResult::LoxError {
+            span,
+            code,
             message: message.to_string(),
-        };
-        e.report("");
-        e
+        }
     }
 
-    pub fn parse_error(token: &Token, message: &str) -> LoxResult {
+    pub fn parse_error(token: &Token, code: &'static str, message: &str) -> LoxResult {
         // parsing error; cite the incorrect token in error message
-        let e = LoxResult::LoxParseError {
+        LoxResult::LoxParseError {
             token: token.duplicate(),
+            code,
             message: message.to_string(),
-        };
-        e.report("");
-        e
+        }
     }
 
-    pub fn resolver_error(token: &Token, message: &str) -> LoxResult {
-        let e = LoxResult::LoxResolverError {
+    pub fn resolver_error(token: &Token, code: &'static str, message: &str) -> LoxResult {
+        LoxResult::LoxResolverError {
             token: token.duplicate(),
+            code,
             message: message.to_string(),
-        };
-        e.report("");
-        e
+        }
     }
 
-    pub fn runtime_error(token: &Token, message: &str) -> LoxResult {
+    pub fn runtime_error(token: &Token, code: &'static str, message: &str) -> LoxResult {
         // runtime error; cite in correct expression in error message
-        let e = LoxResult::LoxRuntimeError {
+        LoxResult::raised(token, ErrorMessage::new(code, message))
+    }
+
+    pub fn raised(token: &Token, error: ErrorMessage) -> LoxResult {
+        // an operator or native failed; cite the token that invoked it
+        LoxResult::LoxRuntimeError {
             token: token.duplicate(),
-            message: message.to_string(),
-        };
-        e.report("");
-        e
+            code: error.code,
+            message: error.message,
+            trace: Vec::new(),
+        }
     }
 
-    pub fn system_error(message: &str) -> LoxResult {
-        let e = LoxResult::LoxSystemError {
+    pub fn system_error(code: &'static str, message: &str) -> LoxResult {
+        LoxResult::LoxSystemError {
+            code,
             message: message.to_string(),
-        };
-        e.report("");
-        e
+        }
     }
 
-    fn report(&self, loc: &str) {
-        // print the appropriate error message
-        match self {
-            LoxResult::LoxParseError { token, message }
-            | LoxResult::LoxResolverError { token, message } 
-            | LoxResult::LoxRuntimeError { token, message } => {
-                if token.is(TokenType::Eof) {
-                    eprintln!("[line {}] at end: {}", token.line, message);
-                } else {
-                    eprintln!(
-                        "[line {}] at '{}': {}",
-                        token.line,
-                        token.as_string(),
-                        message
-                    );
-                }
+    pub fn fuel_exhausted(span: Span) -> LoxResult {
+        // the host's execution budget ran out while running this node
+        LoxResult::LoxFuelExhausted { span }
+    }
+
+    pub fn too_much_nesting(kind: DiagnosticKind, span: Span) -> LoxResult {
+        // the program nests deeper than the stack left for walking it
+        LoxResult::LoxNestingError { kind, span }
+    }
+
+    pub fn diagnostic(&self) -> Option<Diagnostic> {
+        // describe the error as data; control flow results have no diagnostic
+        let diagnostic = match self {
+            LoxResult::LoxError { span, code, message } => {
+                Diagnostic::new(DiagnosticKind::Lexical, Severity::Error, Some(code), message, span.line, None)
+                    .with_span(*span)
+            }
+            LoxResult::LoxParseError { token, code, message } => {
+                Diagnostic::at(DiagnosticKind::Syntax, Severity::Error, Some(code), message, token)
+            }
+            LoxResult::LoxResolverError { token, code, message } => {
+                Diagnostic::at(DiagnosticKind::Resolution, Severity::Error, Some(code), message, token)
+            }
+            LoxResult::LoxRuntimeError { token, code, message, trace } => {
+                Diagnostic::at(DiagnosticKind::Runtime, Severity::Error, *code, message, token).with_trace(trace)
+            }
+            LoxResult::LoxFuelExhausted { span } => {
+                Diagnostic::new(DiagnosticKind::Runtime, Severity::Error, Some("L0310"), "Execution budget exhausted.", span.line, None)
+                    .with_span(*span)
             }
-            LoxResult::LoxError { line, message } => {
-                eprintln!("[line {}] Error{}: {}", *line, loc, message);
+            LoxResult::LoxNestingError { kind, span } => {
+                Diagnostic::new(*kind, Severity::Error, Some("L0111"), "Too much nesting.", span.line, None).with_span(*span)
             }
-            LoxResult::LoxSystemError { message } => {
-                eprintln!("System Error: {message}")
+            LoxResult::LoxSystemError { code, message } => {
+                Diagnostic::new(DiagnosticKind::System, Severity::Error, Some(code), message, 0, None)
             }
             LoxResult::Break 
-            | LoxResult::Return { value: _ } => {}
+            | LoxResult::Continue
+            | LoxResult::Interrupted
+            | LoxResult::Return { value: _ } => return None,
         };
+        Some(diagnostic)
     }
 }
@@ -0,0 +1,777 @@
+use crate::diagnostic::*;
+
+pub struct ErrorCode {
+    pub code: &'static str,
+    pub kind: DiagnosticKind,
+    pub summary: &'static str,
+    pub explanation: &'static str,
+}
+
+impl ErrorCode {
+    pub fn find(code: &str) -> Option<&'static ErrorCode> {
+        CODES.iter().find(|c| c.code.eq_ignore_ascii_case(code))
+    }
+}
+
+const CODES: &[ErrorCode] = &[
+    ErrorCode {
+        code: "L0001",
+        kind: DiagnosticKind::Lexical,
+        summary: "unexpected character",
+        explanation: "\
+The scanner found a character that can't start any token.
+
+    var price = 5$;
+
+Lox has no '$', '@', '#' or similar operators. Remove the character, or put
+it inside a string literal if it was meant to be text.",
+    },
+    ErrorCode {
+        code: "L0002",
+        kind: DiagnosticKind::Lexical,
+        summary: "unterminated string",
+        explanation: "\
+A string literal was opened with '\"' but never closed before the end of the
+file.
+
+    print \"hello;
+
+Add the closing quote. Strings may span several lines, so the error is
+reported where the string started. A '${' inside a string also needs the '}'
+that ends the interpolated expression.",
+    },
+    ErrorCode {
+        code: "L0003",
+        kind: DiagnosticKind::Lexical,
+        summary: "unterminated block comment",
+        explanation: "\
+A '/*' comment was never closed with '*/'.
+
+    /* TODO: remove
+    print 1;
+
+Close the comment with '*/'. Block comments nest, so every '/*' inside the
+comment needs its own '*/' as well.",
+    },
+    ErrorCode {
+        code: "L0004",
+        kind: DiagnosticKind::Lexical,
+        summary: "invalid escape sequence",
+        explanation: "\
+A backslash in a string starts an escape sequence, and only these are known:
+\\n, \\t, \\r, \\0, \\\\, \\\", \\$ and \\u{...}.
+
+    print \"C:\\data\";
+
+Write '\\\\' for a literal backslash, or use a raw \"\"\"...\"\"\" string, which
+doesn't process escapes.",
+    },
+    ErrorCode {
+        code: "L0005",
+        kind: DiagnosticKind::Lexical,
+        summary: "invalid Unicode escape",
+        explanation: "\
+A '\\u' escape must be written '\\u{XXXX}' with one to six hex digits, and the
+number must be a Unicode scalar value: at most 10FFFF and not a surrogate
+(D800 to DFFF).
+
+    print \"\\u{D800}\";
+
+Check the code point, and remember the braces.",
+    },
+    ErrorCode {
+        code: "L0101",
+        kind: DiagnosticKind::Syntax,
+        summary: "expected an expression",
+        explanation: "\
+The parser needed a value here, such as a number, string, variable or call,
+but found something else.
+
+    var total = ;
+    print 1 + ;
+
+Supply the missing operand, or remove the stray operator.",
+    },
+    ErrorCode {
+        code: "L0102",
+        kind: DiagnosticKind::Syntax,
+        summary: "missing semicolon",
+        explanation: "\
+Statements end with ';'. The parser reached the next token without seeing
+one.
+
+    var a = 1
+    print a;
+
+Add ';' at the end of the previous statement. The error points at the token
+where the semicolon was expected, which is often on the following line.",
+    },
+    ErrorCode {
+        code: "L0103",
+        kind: DiagnosticKind::Syntax,
+        summary: "unbalanced parentheses",
+        explanation: "\
+A '(' or ')' is missing. Conditions of 'if' and 'while', the clauses of 'for',
+parameter lists and argument lists must all be wrapped in parentheses.
+
+    if x > 1 { print x; }
+    print max(1, 2;
+
+Add the missing parenthesis.",
+    },
+    ErrorCode {
+        code: "L0104",
+        kind: DiagnosticKind::Syntax,
+        summary: "unbalanced braces",
+        explanation: "\
+A '{' or '}' is missing. Function, method and class bodies must be blocks,
+and every block must be closed.
+
+    fun greet() print \"hi\";
+    class Point { init() {}
+
+Add the missing brace.",
+    },
+    ErrorCode {
+        code: "L0105",
+        kind: DiagnosticKind::Syntax,
+        summary: "invalid assignment target",
+        explanation: "\
+Only variables and fields can be assigned to.
+
+    1 = x;
+    a + b = c;
+    f() = 2;
+
+Assign to a variable name, or to a field with 'object.field = value'.",
+    },
+    ErrorCode {
+        code: "L0106",
+        kind: DiagnosticKind::Syntax,
+        summary: "too many parameters or arguments",
+        explanation: "\
+Functions can take at most 255 parameters, and calls can pass at most 255
+arguments.
+
+Group related values into an instance and pass that instead.",
+    },
+    ErrorCode {
+        code: "L0107",
+        kind: DiagnosticKind::Syntax,
+        summary: "expected a name",
+        explanation: "\
+An identifier was required here. Declarations, parameters, property accesses
+and superclass references all need a name made of letters, digits and '_'
+that doesn't start with a digit or collide with a keyword.
+
+    var 1st = 1;
+    class {}
+    print point.;
+
+Choose a valid identifier.",
+    },
+    ErrorCode {
+        code: "L0108",
+        kind: DiagnosticKind::Syntax,
+        summary: "bare 'super'",
+        explanation: "\
+'super' is only meaningful when looking up a method on the superclass, so it
+must be followed by '.' and a method name.
+
+    class B < A {
+      init() { super(); }
+    }
+
+Write 'super.init()' instead.",
+    },
+    ErrorCode {
+        code: "L0109",
+        kind: DiagnosticKind::Syntax,
+        summary: "unclosed bracket",
+        explanation: "\
+A list literal or an index expression was opened with '[' but not closed.
+
+    var xs = [1, 2, 3;
+    print xs[0;
+
+Add the missing ']'.",
+    },
+    ErrorCode {
+        code: "L0110",
+        kind: DiagnosticKind::Syntax,
+        summary: "map entry without ':'",
+        explanation: "\
+Each entry in a map literal is a key and a value separated by ':'.
+
+    var m = {\"a\" 1};
+
+Write '{\"a\": 1}' instead.",
+    },
+    ErrorCode {
+        code: "L0111",
+        kind: DiagnosticKind::Syntax,
+        summary: "nesting too deep",
+        explanation: "\
+Statements or expressions are nested more deeply than Lox allows.
+
+    print ((((((((((1))))))))));
+
+Nesting is limited to 256 levels, and to less when each level is costly
+for the interpreter, such as a long chain of calls or operators. Programs
+that nest this deeply are usually generated; split the expression into
+variables or the code into functions.",
+    },
+    ErrorCode {
+        code: "L0112",
+        kind: DiagnosticKind::Syntax,
+        summary: "missing '=>'",
+        explanation: "\
+An arrow function's parameter list must be followed by '=>' and the body.
+
+    var twice = (x) x * 2;
+
+Write '(x) => x * 2' instead.",
+    },
+    ErrorCode {
+        code: "L0201",
+        kind: DiagnosticKind::Resolution,
+        summary: "return outside of a function",
+        explanation: "\
+'return' can only appear inside a function or method body.
+
+    return 1;
+
+To stop a script early, restructure it with 'if', or move the code into a
+function.",
+    },
+    ErrorCode {
+        code: "L0202",
+        kind: DiagnosticKind::Resolution,
+        summary: "value returned from an initializer",
+        explanation: "\
+An 'init' method always returns the new instance, so it can't return
+anything else.
+
+    class Point {
+      init(x) { this.x = x; return x; }
+    }
+
+Use a bare 'return;' to leave the initializer early.",
+    },
+    ErrorCode {
+        code: "L0203",
+        kind: DiagnosticKind::Resolution,
+        summary: "break outside of a loop",
+        explanation: "\
+'break' jumps out of the innermost 'while' or 'for' loop, so it is only
+allowed inside one. A function body starts outside of any loop, even when
+the function is declared inside a loop.
+
+    if (done) break;
+
+Remove the 'break', or move it into a loop.",
+    },
+    ErrorCode {
+        code: "L0204",
+        kind: DiagnosticKind::Resolution,
+        summary: "'this' outside of a class",
+        explanation: "\
+'this' refers to the instance a method was called on, so it can only be
+used inside a method.
+
+    fun describe() { print this.name; }
+
+Move the function into a class, or pass the instance as a parameter.",
+    },
+    ErrorCode {
+        code: "L0205",
+        kind: DiagnosticKind::Resolution,
+        summary: "'super' outside of a class",
+        explanation: "\
+'super' looks up methods on the superclass of the enclosing class, so it can
+only be used inside a method.
+
+    fun f() { super.init(); }
+
+Move the code into a method of a subclass.",
+    },
+    ErrorCode {
+        code: "L0206",
+        kind: DiagnosticKind::Resolution,
+        summary: "'super' without a superclass",
+        explanation: "\
+The class using 'super' doesn't inherit from anything, so there is no
+superclass to look methods up on.
+
+    class A {
+      init() { super.init(); }
+    }
+
+Declare a superclass with 'class A < Base', or call the method directly.",
+    },
+    ErrorCode {
+        code: "L0207",
+        kind: DiagnosticKind::Resolution,
+        summary: "class inherits from itself",
+        explanation: "\
+A class was named as its own superclass.
+
+    class Node < Node {}
+
+Inherit from a different, previously declared class.",
+    },
+    ErrorCode {
+        code: "L0208",
+        kind: DiagnosticKind::Resolution,
+        summary: "duplicate local declaration",
+        explanation: "\
+Two local variables with the same name were declared in the same block.
+Globals may be redeclared, but locals may not.
+
+    {
+      var a = 1;
+      var a = 2;
+    }
+
+Rename one of them, or assign to the existing variable with 'a = 2;'.",
+    },
+    ErrorCode {
+        code: "L0209",
+        kind: DiagnosticKind::Resolution,
+        summary: "local read in its own initializer",
+        explanation: "\
+A local variable was used in the expression that initializes it. At that
+point the new variable exists but has no value yet.
+
+    var a = 1;
+    {
+      var a = a + 1;
+    }
+
+Give the new variable a different name.",
+    },
+    ErrorCode {
+        code: "L0210",
+        kind: DiagnosticKind::Resolution,
+        summary: "wrong number of arguments",
+        explanation: "\
+A call passes a different number of arguments than the callee takes, and the
+callee is known before the program runs: a top-level function or class that
+is declared once and never reassigned, or a built-in function.
+
+    fun add(a, b) { return a + b; }
+    print add(1);
+
+Pass exactly one argument per parameter. Calling a class passes the
+arguments to its 'init' method. The same mistake through a variable or
+field is reported when the call runs instead, as L0307.",
+    },
+    ErrorCode {
+        code: "L0211",
+        kind: DiagnosticKind::Resolution,
+        summary: "continue outside of a loop",
+        explanation: "\
+'continue' skips to the next iteration of the innermost 'while' or 'for'
+loop, so it is only allowed inside one. As with 'break', a function body
+starts outside of any loop.
+
+    fun check(n) {
+      if (n < 0) continue;
+    }
+
+Use 'return' to leave a function early.",
+    },
+    ErrorCode {
+        code: "L0212",
+        kind: DiagnosticKind::Resolution,
+        summary: "malformed class body",
+        explanation: "\
+A class body held something other than a method. The parser only produces
+methods there, so this points to a bug in the interpreter rather than in the
+program; please report it with the code that caused it.",
+    },
+    ErrorCode {
+        code: "L0301",
+        kind: DiagnosticKind::Runtime,
+        summary: "operand of the wrong type",
+        explanation: "\
+An operator was applied to values it doesn't support. Arithmetic and
+comparison need numbers; '+' also joins strings, and '*' repeats a string a
+number of times.
+
+    print \"a\" - 1;
+    print -\"a\";
+
+Convert or check the values before operating on them.",
+    },
+    ErrorCode {
+        code: "L0302",
+        kind: DiagnosticKind::Runtime,
+        summary: "division by zero",
+        explanation: "\
+The right-hand side of '/' was zero.
+
+    print 1 / 0;
+
+Check the divisor before dividing.",
+    },
+    ErrorCode {
+        code: "L0303",
+        kind: DiagnosticKind::Runtime,
+        summary: "undefined variable",
+        explanation: "\
+A variable was read or assigned before any declaration of it ran. Globals
+are looked up when the code runs, so a function may refer to a global
+declared later, but it must be declared before the function is called.
+
+    print count;
+    var count = 0;
+
+Declare the variable with 'var' first, or check the spelling.",
+    },
+    ErrorCode {
+        code: "L0304",
+        kind: DiagnosticKind::Runtime,
+        summary: "undefined property",
+        explanation: "\
+The instance has no field with this name, and its class and superclasses
+have no method with it either.
+
+    class Point {}
+    print Point().x;
+
+Set the field before reading it, for example in 'init', or check the
+spelling.",
+    },
+    ErrorCode {
+        code: "L0305",
+        kind: DiagnosticKind::Runtime,
+        summary: "property access on a non-instance",
+        explanation: "\
+Properties can only be read from or written to instances of a class.
+
+    var n = 1;
+    print n.size;
+
+Make sure the value is an instance before using '.' on it.",
+    },
+    ErrorCode {
+        code: "L0306",
+        kind: DiagnosticKind::Runtime,
+        summary: "call of a non-callable value",
+        explanation: "\
+Only functions, methods and classes can be called.
+
+    var name = \"lox\";
+    name();
+
+Check that the value is what you expect, and that a field doesn't shadow a
+method of the same name.",
+    },
+    ErrorCode {
+        code: "L0307",
+        kind: DiagnosticKind::Runtime,
+        summary: "wrong number of arguments",
+        explanation: "\
+A function was called with a different number of arguments than it has
+parameters. Calling a class passes the arguments to its 'init' method.
+
+    fun add(a, b) { return a + b; }
+    add(1);
+
+Pass exactly one argument per parameter.",
+    },
+    ErrorCode {
+        code: "L0308",
+        kind: DiagnosticKind::Runtime,
+        summary: "superclass is not a class",
+        explanation: "\
+The name after '<' in a class declaration refers to a value that isn't a
+class.
+
+    var Base = 1;
+    class A < Base {}
+
+Inherit from a class.",
+    },
+    ErrorCode {
+        code: "L0309",
+        kind: DiagnosticKind::Runtime,
+        summary: "stack overflow",
+        explanation: "\
+Calls nested deeper than the interpreter allows, which almost always means
+a recursive function never reaches its base case.
+
+    fun loop(n) { return loop(n + 1); }
+    loop(0);
+
+Check the recursion's stopping condition, or rewrite it as a loop.",
+    },
+    ErrorCode {
+        code: "L0310",
+        kind: DiagnosticKind::Runtime,
+        summary: "execution budget exhausted",
+        explanation: "\
+The program ran more steps than the host allowed it. Hosts that embed the
+interpreter can set such a budget to stop runaway scripts.
+
+    while (true) {}
+
+Look for loops that never finish, or ask the host for a larger budget.",
+    },
+    ErrorCode {
+        code: "L0311",
+        kind: DiagnosticKind::Runtime,
+        summary: "comparison of different types",
+        explanation: "\
+'==' and '!=' compare values of the same type; only 'nil' may be compared
+with anything.
+
+    print 1 == \"1\";
+
+Convert one side so both have the same type.",
+    },
+    ErrorCode {
+        code: "L0312",
+        kind: DiagnosticKind::Runtime,
+        summary: "string too long",
+        explanation: "\
+Repeating a string would have produced more text than the interpreter will
+hold in one value.
+
+    print \"ab\" * 1000000000;
+
+Use a smaller repeat count.",
+    },
+    ErrorCode {
+        code: "L0313",
+        kind: DiagnosticKind::Runtime,
+        summary: "index out of bounds",
+        explanation: "\
+An index was outside the list. Indexes start at 0 and must be less than the
+list's length; 'insert' and 'slice' also accept the length itself.
+
+    var xs = [1, 2];
+    print xs[2];
+
+Check the index against 'len(xs)' first.",
+    },
+    ErrorCode {
+        code: "L0314",
+        kind: DiagnosticKind::Runtime,
+        summary: "index is not a whole number",
+        explanation: "\
+Lists are indexed by whole numbers. Fractions, strings and other values
+can't be used as an index.
+
+    var xs = [1, 2];
+    print xs[0.5];
+
+Use an integer-valued number.",
+    },
+    ErrorCode {
+        code: "L0315",
+        kind: DiagnosticKind::Runtime,
+        summary: "indexing or slicing an unsupported value",
+        explanation: "\
+Lists, maps and strings can be indexed with '[...]', but only lists and
+maps can be assigned to by index. Only lists and strings can be sliced with
+'[start:end]'.
+
+    var n = 3;
+    print n[0];
+
+Make sure the value has the type you expect.",
+    },
+    ErrorCode {
+        code: "L0316",
+        kind: DiagnosticKind::Runtime,
+        summary: "wrong argument type for a built-in",
+        explanation: "\
+A built-in function was called with a value of the wrong type.
+
+    push(\"abc\", 1);
+
+Pass a value of the type the function expects.",
+    },
+    ErrorCode {
+        code: "L0317",
+        kind: DiagnosticKind::Runtime,
+        summary: "unhashable map key",
+        explanation: "\
+Map keys must be strings, numbers, booleans or nil. Lists, maps, functions,
+classes and instances can't be used as keys.
+
+    var m = {};
+    m[[1, 2]] = true;
+
+Use a string or number that identifies the value instead.",
+    },
+    ErrorCode {
+        code: "L0318",
+        kind: DiagnosticKind::Runtime,
+        summary: "missing map key",
+        explanation: "\
+A map was read with a key it doesn't contain.
+
+    var m = {\"a\": 1};
+    print m[\"b\"];
+
+Check with 'has(m, key)' first, or assign the key before reading it.",
+    },
+    ErrorCode {
+        code: "L0319",
+        kind: DiagnosticKind::Runtime,
+        summary: "assigning into a string",
+        explanation: "\
+Strings are immutable, so a character can't be replaced by index.
+
+    var s = \"cat\";
+    s[0] = \"b\";
+
+Build a new string instead, for example with slicing: \"b\" + s[1:].",
+    },
+    ErrorCode {
+        code: "L0320",
+        kind: DiagnosticKind::Runtime,
+        summary: "internal interpreter error",
+        explanation: "\
+The interpreter reached a state the parser and resolver should have ruled
+out, such as an operator it doesn't know or a 'super' with no superclass
+to find. This points to a bug in the interpreter rather than in the program;
+please report it with the code that caused it.",
+    },
+    ErrorCode {
+        code: "L0401",
+        kind: DiagnosticKind::Resolution,
+        summary: "unused local variable",
+        explanation: "\
+A local variable is declared but its value is never read. Assigning to it
+doesn't count as a use.
+
+    fun area(w, h) {
+      var unused = w * 2;
+      return w * h;
+    }
+
+Remove the variable, or start its name with '_' if it is kept on purpose.
+This is a warning; the program still runs.",
+    },
+    ErrorCode {
+        code: "L0402",
+        kind: DiagnosticKind::Resolution,
+        summary: "unused parameter",
+        explanation: "\
+A function or method never reads one of its parameters.
+
+    fun greet(name, greeting) {
+      print \"hello \" + name;
+    }
+
+Remove the parameter and update the callers, or start its name with '_' if
+the signature has to stay as it is. This is a warning; the program still
+runs.",
+    },
+    ErrorCode {
+        code: "L0403",
+        kind: DiagnosticKind::Resolution,
+        summary: "unused local function",
+        explanation: "\
+A function declared inside a block or another function is never called or
+otherwise referred to outside its own body.
+
+    fun outer() {
+      fun helper(n) { if (n > 0) helper(n - 1); }
+      return 1;
+    }
+
+Calls a function makes to itself don't count, since nothing outside can
+start them. Remove the function, or start its name with '_' if it is kept on purpose.
+This is a warning; the program still runs.",
+    },
+    ErrorCode {
+        code: "L0404",
+        kind: DiagnosticKind::Resolution,
+        summary: "unreachable code",
+        explanation: "\
+Statements follow a 'return' or 'break' in the same block, so they can never
+run.
+
+    fun sign(n) {
+      return n < 0;
+      print \"checked\";
+    }
+
+Remove the dead statements, or move them before the 'return' or 'break'.
+This is a warning; the program still runs.",
+    },
+    ErrorCode {
+        code: "L0405",
+        kind: DiagnosticKind::Resolution,
+        summary: "shadowed variable",
+        explanation: "\
+A local declaration reuses the name of a variable from an enclosing scope or
+of a global. Inside the block, the outer variable can no longer be reached,
+which is a common source of bugs when an assignment was intended.
+
+    var total = 0;
+    {
+      var total = 5;
+    }
+    print total;
+
+Assign to the outer variable instead of declaring a new one, or give the
+inner one a different name. Parameters and names starting with '_' are not
+reported. This is a warning; the program still runs.",
+    },
+    ErrorCode {
+        code: "L0406",
+        kind: DiagnosticKind::Resolution,
+        summary: "value assigned but never read",
+        explanation: "\
+A local variable was assigned, but the value is never read before the
+variable goes out of scope or is assigned again.
+
+    fun f() {
+      var result = 1;
+      print result;
+      result = 2;
+      result = 3;
+    }
+
+Both assignments are reported. An assignment is only known to be replaced
+when the next one follows it unconditionally; one in an 'if' branch, a loop
+or after 'and'/'or' is not. Remove the assignment, or use the value. Variables that closures capture
+are not reported, since the closure may read them later. This is a warning;
+the program still runs.",
+    },
+    ErrorCode {
+        code: "L0501",
+        kind: DiagnosticKind::System,
+        summary: "output could not be written",
+        explanation: "\
+'print' failed to write to standard output or to the writer the host set.
+A closed pipe, such as piping into a command that exits early, is the usual
+cause.
+
+    rlox script.lox | head -1
+
+The run stops with exit code 74.",
+    },
+    ErrorCode {
+        code: "L0502",
+        kind: DiagnosticKind::System,
+        summary: "script could not be read",
+        explanation: "\
+The script file named on the command line doesn't exist or can't be read.
+Check the path and the file's permissions.",
+    },
+    ErrorCode {
+        code: "L0503",
+        kind: DiagnosticKind::System,
+        summary: "clock unavailable",
+        explanation: "\
+'clock()' could not read the system time, usually because the clock is set
+before 1970.",
+    },
+];
@@ -2,6 +2,7 @@ use std::rc::Rc;
 use std::hash::{Hash, Hasher};
 use crate::error::*;
 use crate::token::*;
+use crate::stmt::*;
 
 pub enum Expr {
     Assign(Rc<AssignExpr>),
@@ -9,9 +10,16 @@ pub enum Expr {
     Call(Rc<CallExpr>),
     Get(Rc<GetExpr>),
     Grouping(Rc<GroupingExpr>),
+    Index(Rc<IndexExpr>),
+    IndexSet(Rc<IndexSetExpr>),
+    Interpolation(Rc<InterpolationExpr>),
+    Lambda(Rc<LambdaExpr>),
+    List(Rc<ListExpr>),
     Literal(Rc<LiteralExpr>),
+    Map(Rc<MapExpr>),
     Logical(Rc<LogicalExpr>),
     Set(Rc<SetExpr>),
+    Slice(Rc<SliceExpr>),
     Super(Rc<SuperExpr>),
     This(Rc<ThisExpr>),
     Unary(Rc<UnaryExpr>),
@@ -26,9 +34,16 @@ impl PartialEq for Expr {
             (Expr::Call(a), Expr::Call(b)) => Rc::ptr_eq(a, b),
             (Expr::Get(a), Expr::Get(b)) => Rc::ptr_eq(a, b),
             (Expr::Grouping(a), Expr::Grouping(b)) => Rc::ptr_eq(a, b),
+            (Expr::Index(a), Expr::Index(b)) => Rc::ptr_eq(a, b),
+            (Expr::IndexSet(a), Expr::IndexSet(b)) => Rc::ptr_eq(a, b),
+            (Expr::Interpolation(a), Expr::Interpolation(b)) => Rc::ptr_eq(a, b),
+            (Expr::Lambda(a), Expr::Lambda(b)) => Rc::ptr_eq(a, b),
+            (Expr::List(a), Expr::List(b)) => Rc::ptr_eq(a, b),
             (Expr::Literal(a), Expr::Literal(b)) => Rc::ptr_eq(a, b),
+            (Expr::Map(a), Expr::Map(b)) => Rc::ptr_eq(a, b),
             (Expr::Logical(a), Expr::Logical(b)) => Rc::ptr_eq(a, b),
             (Expr::Set(a), Expr::Set(b)) => Rc::ptr_eq(a, b),
+            (Expr::Slice(a), Expr::Slice(b)) => Rc::ptr_eq(a, b),
             (Expr::Super(a), Expr::Super(b)) => Rc::ptr_eq(a, b),
             (Expr::This(a), Expr::This(b)) => Rc::ptr_eq(a, b),
             (Expr::Unary(a), Expr::Unary(b)) => Rc::ptr_eq(a, b),
@@ -58,15 +73,36 @@ impl Hash for Expr {
             Expr::Grouping(a) => {
                 hasher.write_usize(Rc::as_ptr(a) as usize);
             }
+            Expr::Index(a) => {
+                hasher.write_usize(Rc::as_ptr(a) as usize);
+            }
+            Expr::IndexSet(a) => {
+                hasher.write_usize(Rc::as_ptr(a) as usize);
+            }
+            Expr::Interpolation(a) => {
+                hasher.write_usize(Rc::as_ptr(a) as usize);
+            }
+            Expr::Lambda(a) => {
+                hasher.write_usize(Rc::as_ptr(a) as usize);
+            }
+            Expr::List(a) => {
+                hasher.write_usize(Rc::as_ptr(a) as usize);
+            }
             Expr::Literal(a) => {
                 hasher.write_usize(Rc::as_ptr(a) as usize);
             }
+            Expr::Map(a) => {
+                hasher.write_usize(Rc::as_ptr(a) as usize);
+            }
             Expr::Logical(a) => {
                 hasher.write_usize(Rc::as_ptr(a) as usize);
             }
             Expr::Set(a) => {
                 hasher.write_usize(Rc::as_ptr(a) as usize);
             }
+            Expr::Slice(a) => {
+                hasher.write_usize(Rc::as_ptr(a) as usize);
+            }
             Expr::Super(a) => {
                 hasher.write_usize(Rc::as_ptr(a) as usize);
             }
@@ -91,75 +127,167 @@ impl Expr {
             Expr::Call(v) => expr_visitor.visit_call_expr(wrapper, v),
             Expr::Get(v) => expr_visitor.visit_get_expr(wrapper, v),
             Expr::Grouping(v) => expr_visitor.visit_grouping_expr(wrapper, v),
+            Expr::Index(v) => expr_visitor.visit_index_expr(wrapper, v),
+            Expr::IndexSet(v) => expr_visitor.visit_index_set_expr(wrapper, v),
+            Expr::Interpolation(v) => expr_visitor.visit_interpolation_expr(wrapper, v),
+            Expr::Lambda(v) => expr_visitor.visit_lambda_expr(wrapper, v),
+            Expr::List(v) => expr_visitor.visit_list_expr(wrapper, v),
             Expr::Literal(v) => expr_visitor.visit_literal_expr(wrapper, v),
+            Expr::Map(v) => expr_visitor.visit_map_expr(wrapper, v),
             Expr::Logical(v) => expr_visitor.visit_logical_expr(wrapper, v),
             Expr::Set(v) => expr_visitor.visit_set_expr(wrapper, v),
+            Expr::Slice(v) => expr_visitor.visit_slice_expr(wrapper, v),
             Expr::Super(v) => expr_visitor.visit_super_expr(wrapper, v),
             Expr::This(v) => expr_visitor.visit_this_expr(wrapper, v),
             Expr::Unary(v) => expr_visitor.visit_unary_expr(wrapper, v),
             Expr::Variable(v) => expr_visitor.visit_variable_expr(wrapper, v),
         }
     }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Assign(v) => v.span,
+            Expr::Binary(v) => v.span,
+            Expr::Call(v) => v.span,
+            Expr::Get(v) => v.span,
+            Expr::Grouping(v) => v.span,
+            Expr::Index(v) => v.span,
+            Expr::IndexSet(v) => v.span,
+            Expr::Interpolation(v) => v.span,
+            Expr::Lambda(v) => v.span,
+            Expr::List(v) => v.span,
+            Expr::Literal(v) => v.span,
+            Expr::Map(v) => v.span,
+            Expr::Logical(v) => v.span,
+            Expr::Set(v) => v.span,
+            Expr::Slice(v) => v.span,
+            Expr::Super(v) => v.span,
+            Expr::This(v) => v.span,
+            Expr::Unary(v) => v.span,
+            Expr::Variable(v) => v.span,
+        }
+    }
 }
 
 pub struct AssignExpr {
     pub name: Token,
     pub value: Rc<Expr>,
+    pub span: Span,
 }
 
 pub struct BinaryExpr {
     pub left: Rc<Expr>,
     pub operator: Token,
     pub right: Rc<Expr>,
+    pub span: Span,
 }
 
 pub struct CallExpr {
     pub callee: Rc<Expr>,
     pub paren: Token,
     pub arguments: Vec<Rc<Expr>>,
+    pub span: Span,
 }
 
 pub struct GetExpr {
     pub object: Rc<Expr>,
     pub name: Token,
+    pub span: Span,
 }
 
 pub struct GroupingExpr {
     pub expression: Rc<Expr>,
+    pub span: Span,
+}
+
+pub struct IndexExpr {
+    pub object: Rc<Expr>,
+    pub bracket: Token,
+    pub index: Rc<Expr>,
+    pub span: Span,
+}
+
+pub struct IndexSetExpr {
+    pub object: Rc<Expr>,
+    pub bracket: Token,
+    pub index: Rc<Expr>,
+    pub value: Rc<Expr>,
+    pub span: Span,
+}
+
+pub struct InterpolationExpr {
+    pub token: Token,
+    pub parts: Vec<Rc<Expr>>,
+    pub span: Span,
+}
+
+pub struct LambdaExpr {
+    pub keyword: Token,
+    pub params: Rc<Vec<Token>>,
+    pub body: Rc<Vec<Rc<Stmt>>>,
+    pub span: Span,
+}
+
+pub struct ListExpr {
+    pub bracket: Token,
+    pub elements: Vec<Rc<Expr>>,
+    pub span: Span,
 }
 
 pub struct LiteralExpr {
     pub value: Option<Object>,
+    pub span: Span,
+}
+
+pub struct MapExpr {
+    pub brace: Token,
+    pub keys: Vec<Rc<Expr>>,
+    pub values: Vec<Rc<Expr>>,
+    pub span: Span,
 }
 
 pub struct LogicalExpr {
     pub left: Rc<Expr>,
     pub operator: Token,
     pub right: Rc<Expr>,
+    pub span: Span,
 }
 
 pub struct SetExpr {
     pub object: Rc<Expr>,
     pub name: Token,
     pub value: Rc<Expr>,
+    pub span: Span,
+}
+
+pub struct SliceExpr {
+    pub object: Rc<Expr>,
+    pub bracket: Token,
+    pub start: Option<Rc<Expr>>,
+    pub end: Option<Rc<Expr>>,
+    pub span: Span,
 }
 
 pub struct SuperExpr {
     pub keyword: Token,
     pub method: Token,
+    pub span: Span,
 }
 
 pub struct ThisExpr {
     pub keyword: Token,
+    pub span: Span,
 }
 
 pub struct UnaryExpr {
     pub operator: Token,
     pub right: Rc<Expr>,
+    pub span: Span,
 }
 
 pub struct VariableExpr {
     pub name: Token,
+    pub span: Span,
 }
 
 pub trait ExprVisitor<T> {
@@ -168,9 +296,16 @@ pub trait ExprVisitor<T> {
     fn visit_call_expr(&self, wrapper: Rc<Expr>, expr: &CallExpr) -> Result<T, LoxResult>;
     fn visit_get_expr(&self, wrapper: Rc<Expr>, expr: &GetExpr) -> Result<T, LoxResult>;
     fn visit_grouping_expr(&self, wrapper: Rc<Expr>, expr: &GroupingExpr) -> Result<T, LoxResult>;
+    fn visit_index_expr(&self, wrapper: Rc<Expr>, expr: &IndexExpr) -> Result<T, LoxResult>;
+    fn visit_index_set_expr(&self, wrapper: Rc<Expr>, expr: &IndexSetExpr) -> Result<T, LoxResult>;
+    fn visit_interpolation_expr(&self, wrapper: Rc<Expr>, expr: &InterpolationExpr) -> Result<T, LoxResult>;
+    fn visit_lambda_expr(&self, wrapper: Rc<Expr>, expr: &LambdaExpr) -> Result<T, LoxResult>;
+    fn visit_list_expr(&self, wrapper: Rc<Expr>, expr: &ListExpr) -> Result<T, LoxResult>;
     fn visit_literal_expr(&self, wrapper: Rc<Expr>, expr: &LiteralExpr) -> Result<T, LoxResult>;
+    fn visit_map_expr(&self, wrapper: Rc<Expr>, expr: &MapExpr) -> Result<T, LoxResult>;
     fn visit_logical_expr(&self, wrapper: Rc<Expr>, expr: &LogicalExpr) -> Result<T, LoxResult>;
     fn visit_set_expr(&self, wrapper: Rc<Expr>, expr: &SetExpr) -> Result<T, LoxResult>;
+    fn visit_slice_expr(&self, wrapper: Rc<Expr>, expr: &SliceExpr) -> Result<T, LoxResult>;
     fn visit_super_expr(&self, wrapper: Rc<Expr>, expr: &SuperExpr) -> Result<T, LoxResult>;
     fn visit_this_expr(&self, wrapper: Rc<Expr>, expr: &ThisExpr) -> Result<T, LoxResult>;
     fn visit_unary_expr(&self, wrapper: Rc<Expr>, expr: &UnaryExpr) -> Result<T, LoxResult>;
@@ -1,24 +1,54 @@
+use crate::builtins::*;
 use crate::callable::*;
+use crate::cancel::*;
+use crate::diagnostic::*;
 use crate::environment::*;
 use crate::error::*;
 use crate::expr::*;
 use crate::native_functions::*;
 use crate::lox_function::*;
+use crate::lox_map::*;
 use crate::lox_class::*;
+use crate::stack::*;
 use crate::stmt::*;
 use crate::token::*;
 use crate::token_type::*;
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::collections::HashMap;
+use std::io::{self, Write};
 use std::ops::Deref;
 
 pub struct Interpreter {
     pub globals: Rc<RefCell<Environment>>,
     environment: RefCell<Rc<RefCell<Environment>>>,
     locals: RefCell<HashMap<Rc<Expr>, usize>>,
+    output: RefCell<Box<dyn Write>>,
+    diagnostics: Diagnostics,
+    fuel: RefCell<Option<usize>>,
+    fuel_consumed: RefCell<usize>,
+    cancel: RefCell<CancelHandle>,
+    frames: RefCell<Vec<CallFrame>>,
+    max_call_depth: RefCell<usize>,
+    max_stack: RefCell<usize>,
+    stack_base: RefCell<Option<usize>>,
 }
 
+struct CallFrame {
+    function: String,
+    call_line: usize,
+}
+
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 256;
+
+// How much native stack a run may use before calls fail with "Stack overflow.".
+// One Lox call can take tens of KB in a debug build depending on how deeply its
+// body nests, so the call depth alone can't protect the host. Half of a default
+// 2MB spawned thread leaves room for the host's own frames and for the body of
+// the last call admitted. Hosts that run scripts on a bigger stack, as rlox
+// does, should raise it with set_max_stack.
+pub const DEFAULT_MAX_STACK: usize = 1024 * 1024;
+
 impl StmtVisitor<()> for Interpreter {
     fn visit_class_stmt(&self, _: Rc<Stmt>, stmt: &ClassStmt) -> Result<(), LoxResult> {
         let superclass = if let Some(superclass_expr) = &stmt.superclass {
@@ -26,13 +56,12 @@ impl StmtVisitor<()> for Interpreter {
             
             if let Object::Class(c) = superclass {
                 Some(c)
-            } else if let Expr::Variable(v) = superclass_expr.deref() {
-                return Err(LoxResult::runtime_error(
-                    &v.name,
-                    "Superclass must be a class."
-                ));
             } else {
-                panic!("Could not dereference superclass.")
+                let token = match superclass_expr.deref() {
+                    Expr::Variable(v) => &v.name,
+                    _ => &stmt.name,
+                };
+                return Err(LoxResult::runtime_error(token, "L0308", "Superclass must be a class."));
             }
         } else {
             None
@@ -57,7 +86,7 @@ impl StmtVisitor<()> for Interpreter {
                 );
                 methods.insert(method.name.as_string().to_string(), function);
             } else {
-                panic!("Class method is not a function.");
+                return Err(LoxResult::runtime_error(&stmt.name, "L0320", "Class method is not a function."));
             }
         }
 
@@ -92,6 +121,10 @@ impl StmtVisitor<()> for Interpreter {
         Err(LoxResult::Break)
     }
 
+    fn visit_continue_stmt(&self, _: Rc<Stmt>, _stmt: &ContinueStmt) -> Result<(), LoxResult> {
+        Err(LoxResult::Continue)
+    }
+
     fn visit_block_stmt(&self, _: Rc<Stmt>, stmt: &BlockStmt) -> Result<(), LoxResult> {
         let e = Environment::new_with_enclosing(self.environment.borrow().clone());
         self.execute_block(&stmt.statements, e)
@@ -114,8 +147,11 @@ impl StmtVisitor<()> for Interpreter {
 
     fn visit_print_stmt(&self, _: Rc<Stmt>, stmt: &PrintStmt) -> Result<(), LoxResult> {
         let value = self.evaluate(stmt.expression.clone())?;
-        println!("{value}");
-        Ok(())
+        let text = self.stringify(&value, &stmt.keyword)?;
+        self.output
+            .borrow_mut()
+            .write_all(format!("{text}\n").as_bytes())
+            .map_err(|e| LoxResult::system_error("L0501", &format!("Could not write output: {e}.")))
     }
 
     fn visit_var_stmt(&self, _: Rc<Stmt>, stmt: &VarStmt) -> Result<(), LoxResult> {
@@ -135,8 +171,12 @@ impl StmtVisitor<()> for Interpreter {
         while self.is_truthy(&self.evaluate(stmt.condition.clone())?) {
             match self.execute(stmt.body.clone()) {
                 Err(LoxResult::Break) => break,
+                Err(LoxResult::Continue) | Ok(_) => {}
                 Err(e) => return Err(e),
-                Ok(_) => {}
+            }
+            // a for loop's increment runs after 'continue' too
+            if let Some(increment) = stmt.increment.clone() {
+                self.evaluate(increment)?;
             }
         }
 
@@ -157,13 +197,10 @@ impl ExprVisitor<Object> for Interpreter {
             arguments.push(self.evaluate(argument)?);
         }
 
-        let (callfunc, klass): (Option<Rc<dyn LoxCallable>>, Option<Rc<LoxClass>>) = match callee {
-            Object::Function(f) => (Some(f), None),
+        let (callfunc, klass): (Option<Rc<dyn LoxCallable>>, Option<Rc<LoxClass>>) = match &callee {
+            Object::Function(f) => (Some(f.clone()), None),
             Object::Native(n) => (Some(n.func.clone()), None),
-            Object::Class(c) => {
-                let klass = Rc::clone(&c);
-                (Some(c), Some(klass))
-            }
+            Object::Class(c) => (Some(c.clone()), Some(Rc::clone(c))),
             _ => (None, None),
         };
 
@@ -171,18 +208,42 @@ impl ExprVisitor<Object> for Interpreter {
             if arguments.len() != callfunc.arity() {
                 return Err(LoxResult::runtime_error(
                         &expr.paren,
+                        "L0307",
                         &format!("Expected {} arguments but got {}.", callfunc.arity(), arguments.len()),
                 ))
             };
-            callfunc.call(self, arguments, klass)
+            self.call(&callee, callfunc, arguments, klass, &expr.paren)
         } else {
             Err(LoxResult::runtime_error(
                     &expr.paren,
+                    "L0306",
                     "Can only call functions and classes.",
             ))
         }
     }
 
+    fn visit_slice_expr(&self, _: Rc<Expr>, expr: &SliceExpr) -> Result<Object, LoxResult> {
+        let object = self.evaluate(expr.object.clone())?;
+        let start = match &expr.start {
+            Some(start) => Some(self.evaluate(start.clone())?),
+            None => None,
+        };
+        let end = match &expr.end {
+            Some(end) => Some(self.evaluate(end.clone())?),
+            None => None,
+        };
+        slice_value(&object, start.as_ref(), end.as_ref()).map_err(|e| LoxResult::raised(&expr.bracket, e))
+    }
+
+    fn visit_interpolation_expr(&self, _: Rc<Expr>, expr: &InterpolationExpr) -> Result<Object, LoxResult> {
+        let mut text = String::new();
+        for part in expr.parts.iter() {
+            let value = self.evaluate(part.clone())?;
+            text.push_str(&self.stringify(&value, &expr.token)?);
+        }
+        Ok(Object::Str(text))
+    }
+
     fn visit_get_expr(&self, _: Rc<Expr>, expr: &GetExpr) -> Result<Object, LoxResult> {
         let object = self.evaluate(expr.object.clone())?;
         if let Object::Instance(instance) = object {
@@ -190,6 +251,7 @@ impl ExprVisitor<Object> for Interpreter {
         } else {
             Err(LoxResult::runtime_error(
                     &expr.name,
+                    "L0305",
                     "Only instances have properties.",
             ))
         }
@@ -210,7 +272,7 @@ impl ExprVisitor<Object> for Interpreter {
     }
 
     fn visit_literal_expr(&self, _: Rc<Expr>, expr: &LiteralExpr) -> Result<Object, LoxResult> {
-        Ok(expr.value.clone().unwrap())
+        Ok(expr.value.clone().unwrap_or(Object::Nil))
     }
 
     fn visit_logical_expr(&self, _: Rc<Expr>, expr: &LogicalExpr) -> Result<Object, LoxResult> {
@@ -237,34 +299,99 @@ impl ExprVisitor<Object> for Interpreter {
         } else {
             Err(LoxResult::runtime_error(
                     &expr.name,
+                    "L0305",
                     "Only instances have fields."
             ))
         }
     }
 
     fn visit_super_expr(&self, wrapper: Rc<Expr>, expr: &SuperExpr) -> Result<Object, LoxResult> {
-        let distance = *self.locals.borrow().get(&wrapper).unwrap();
-        let superclass = if let Some(sc) = self.environment.borrow().borrow().get_at(distance, "super").ok() {
-            if let Object::Class(superclass) = sc {
-                superclass
-            } else {
-                panic!("Can't find superclass.");
-            } 
+        // the resolver puts 'super' one scope outside the 'this' of the method
+        let distance = self.locals.borrow().get(&wrapper).copied();
+        let environment = self.environment.borrow().clone();
+        let superclass = match distance.and_then(|d| environment.borrow().get_at(d, "super")) {
+            Some(Object::Class(superclass)) => superclass,
+            _ => return Err(LoxResult::runtime_error(&expr.keyword, "L0320", "Can't find superclass.")),
+        };
+        let object = distance
+            .and_then(|d| d.checked_sub(1))
+            .and_then(|d| environment.borrow().get_at(d, "this"))
+            .unwrap_or(Object::Nil);
+        let method = if let Object::Instance(instance) = object {
+            superclass.bind_method(expr.method.as_string(), &instance)
         } else {
-            panic!("Can't find superclass.");
+            None
         };
-        let object = self.environment.borrow().borrow().get_at(distance - 1, "this").ok().unwrap();
-        if let Some(method) = superclass.find_method(expr.method.as_string()) {
-            if let Object::Function(func) = method {
-                Ok(func.bind(&object))
-            } else {
-                panic!("Method not a function.");
-            }
+        if let Some(method) = method {
+            Ok(method)
         } else {
-            Err(LoxResult::runtime_error(&expr.method, &format!("Undefined property '{}'.", expr.method.as_string())))
+            Err(LoxResult::runtime_error(&expr.method, "L0304", &format!("Undefined property '{}'.", expr.method.as_string())))
         }
     }
 
+    fn visit_list_expr(&self, _: Rc<Expr>, expr: &ListExpr) -> Result<Object, LoxResult> {
+        let mut elements = Vec::new();
+        for element in expr.elements.iter() {
+            elements.push(self.evaluate(element.clone())?);
+        }
+        Ok(Object::list(elements))
+    }
+
+    fn visit_index_expr(&self, _: Rc<Expr>, expr: &IndexExpr) -> Result<Object, LoxResult> {
+        let object = self.evaluate(expr.object.clone())?;
+        let index = self.evaluate(expr.index.clone())?;
+        match object {
+            Object::List(list) => {
+                let list = list.borrow();
+                let i = checked_index(&index, list.len()).map_err(|e| LoxResult::raised(&expr.bracket, e))?;
+                Ok(list[i].clone())
+            }
+            Object::Map(map) => {
+                let value = map.borrow().get(&index).map_err(|e| LoxResult::raised(&expr.bracket, e))?;
+                value.ok_or_else(|| LoxResult::runtime_error(&expr.bracket, "L0318", &format!("Undefined key {}.", index.repr())))
+            }
+            Object::Str(s) => char_at(&s, &index).map_err(|e| LoxResult::raised(&expr.bracket, e)),
+            _ => Err(LoxResult::runtime_error(&expr.bracket, "L0315", "Only lists, maps and strings can be indexed.")),
+        }
+    }
+
+    fn visit_index_set_expr(&self, _: Rc<Expr>, expr: &IndexSetExpr) -> Result<Object, LoxResult> {
+        let object = self.evaluate(expr.object.clone())?;
+        let index = self.evaluate(expr.index.clone())?;
+        let value = self.evaluate(expr.value.clone())?;
+        match object {
+            Object::List(list) => {
+                let mut list = list.borrow_mut();
+                let i = checked_index(&index, list.len()).map_err(|e| LoxResult::raised(&expr.bracket, e))?;
+                list[i] = value.clone();
+                Ok(value)
+            }
+            Object::Map(map) => {
+                map.borrow_mut()
+                    .insert(index, value.clone())
+                    .map_err(|e| LoxResult::raised(&expr.bracket, e))?;
+                Ok(value)
+            }
+            Object::Str(_) => Err(LoxResult::runtime_error(&expr.bracket, "L0319", "Strings can't be modified.")),
+            _ => Err(LoxResult::runtime_error(&expr.bracket, "L0315", "Only lists and maps can be indexed.")),
+        }
+    }
+
+    fn visit_map_expr(&self, _: Rc<Expr>, expr: &MapExpr) -> Result<Object, LoxResult> {
+        let mut map = LoxMap::new();
+        for (key, value) in expr.keys.iter().zip(expr.values.iter()) {
+            let key = self.evaluate(key.clone())?;
+            let value = self.evaluate(value.clone())?;
+            map.insert(key, value).map_err(|e| LoxResult::raised(&expr.brace, e))?;
+        }
+        Ok(Object::map(map))
+    }
+
+    fn visit_lambda_expr(&self, _: Rc<Expr>, expr: &LambdaExpr) -> Result<Object, LoxResult> {
+        let function = LoxFunction::lambda(expr, &self.environment.borrow());
+        Ok(Object::Function(Rc::new(function)))
+    }
+
     fn visit_grouping_expr(&self, _: Rc<Expr>, expr: &GroupingExpr) -> Result<Object, LoxResult> {
         self.evaluate(expr.expression.clone())
     }
@@ -290,11 +417,11 @@ impl ExprVisitor<Object> for Interpreter {
                 Ok(b) => Object::Bool(b),
                 Err(e) => e,
             },
-            _ => Object::ErrorMessage("Invalid operator.".to_string()),
+            _ => Object::ErrorMessage(ErrorMessage::new("L0320", "Invalid operator.")),
         };
 
         match result {
-            Object::ErrorMessage(s) => Err(LoxResult::runtime_error(&expr.operator, &s)),
+            Object::ErrorMessage(e) => Err(LoxResult::raised(&expr.operator, e)),
             _ => Ok(result),
         }
     }
@@ -304,14 +431,14 @@ impl ExprVisitor<Object> for Interpreter {
         let result = match expr.operator.token_type() {
             TokenType::Minus => match right {
                 Object::Num(n) => Object::Num(-n),
-                _ => Object::ErrorMessage("Operand must be number.".to_string()),
+                _ => Object::ErrorMessage(ErrorMessage::new("L0301", "Operand must be number.")),
             },
             TokenType::Bang => Object::Bool(!self.is_truthy(&right)),
-            _ => Object::ErrorMessage("Invalid operator.".to_string()),
+            _ => Object::ErrorMessage(ErrorMessage::new("L0320", "Invalid operator.")),
         };
 
         match result {
-            Object::ErrorMessage(s) => Err(LoxResult::runtime_error(&expr.operator, &s)),
+            Object::ErrorMessage(e) => Err(LoxResult::raised(&expr.operator, e)),
             _ => Ok(result),
         }
     }
@@ -321,27 +448,201 @@ impl ExprVisitor<Object> for Interpreter {
     }
 }
 
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Interpreter {
     pub fn new() -> Interpreter {
         let globals = Rc::new(RefCell::new(Environment::new()));
 
         globals.borrow_mut().define("clock", Object::Native(Rc::new(LoxNative { func: Rc::new(NativeClock {}) })));
 
-        Interpreter {
+        let interpreter = Interpreter {
             globals: Rc::clone(&globals),
             environment: RefCell::new(Rc::clone(&globals)),
             locals: RefCell::new(HashMap::new()),
+            output: RefCell::new(Box::new(io::stdout())),
+            diagnostics: Diagnostics::new(),
+            fuel: RefCell::new(None),
+            fuel_consumed: RefCell::new(0),
+            cancel: RefCell::new(CancelHandle::new()),
+            frames: RefCell::new(Vec::new()),
+            max_call_depth: RefCell::new(DEFAULT_MAX_CALL_DEPTH),
+            max_stack: RefCell::new(DEFAULT_MAX_STACK),
+            stack_base: RefCell::new(None),
+        };
+        define_builtins(&interpreter);
+        interpreter
+    }
+
+    fn call(
+        &self,
+        callee: &Object,
+        callfunc: Rc<dyn LoxCallable>,
+        arguments: Vec<Object>,
+        klass: Option<Rc<LoxClass>>,
+        paren: &Token,
+    ) -> Result<Object, LoxResult> {
+        // arity has already been checked; errors are reported at the call site
+        if self.frames.borrow().len() >= *self.max_call_depth.borrow()
+            || self.stack_used() >= *self.max_stack.borrow()
+        {
+            return Err(LoxResult::runtime_error(paren, "L0309", "Stack overflow."));
+        }
+
+        let function = match callee {
+            Object::Function(f) => f.name().to_string(),
+            Object::Class(c) => c.name().to_string(),
+            _ => "<native>".to_string(),
+        };
+        self.frames.borrow_mut().push(CallFrame { function, call_line: paren.line });
+        let result = callfunc.call(self, arguments, klass).map_err(|e| self.with_backtrace(e));
+        self.frames.borrow_mut().pop();
+
+        match result? {
+            Object::ErrorMessage(e) => Err(LoxResult::raised(paren, e)),
+            value => Ok(value),
+        }
+    }
+
+    pub fn stringify(&self, value: &Object, token: &Token) -> Result<String, LoxResult> {
+        // how print and "${...}" show a value; instances may define toString()
+        if let Object::Instance(instance) = value {
+            let method = instance.klass.bind_method("toString", instance);
+            let callfunc: Option<Rc<dyn LoxCallable>> = match &method {
+                Some(Object::Function(f)) => Some(f.clone()),
+                Some(Object::Native(n)) => Some(n.func.clone()),
+                _ => None,
+            };
+            if let (Some(method), Some(callfunc)) = (&method, callfunc) {
+                if callfunc.arity() == 0 {
+                    return Ok(self.call(method, callfunc, Vec::new(), None, token)?.to_string());
+                }
+            }
         }
+        Ok(value.to_string())
+    }
+
+    pub fn set_max_call_depth(&self, depth: usize) {
+        self.max_call_depth.replace(depth);
+    }
+
+    pub fn max_call_depth(&self) -> usize {
+        *self.max_call_depth.borrow()
+    }
+
+    pub fn set_max_stack(&self, bytes: usize) {
+        // lower this when running scripts on threads with small stacks
+        self.max_stack.replace(bytes);
+    }
+
+    pub fn max_stack(&self) -> usize {
+        *self.max_stack.borrow()
+    }
+
+    fn stack_used(&self) -> usize {
+        // native stack used since interpret() started
+        stack_used(*self.stack_base.borrow())
+    }
+
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.borrow().clone()
+    }
+
+    pub fn set_cancel_handle(&self, handle: CancelHandle) {
+        self.cancel.replace(handle);
+    }
+
+    pub fn set_fuel(&self, fuel: Option<usize>) {
+        // limit how many statements and expressions may still run; None is unlimited.
+        // fuel_consumed starts counting again from here
+        self.fuel.replace(fuel);
+        self.fuel_consumed.replace(0);
+    }
+
+    pub fn remaining_fuel(&self) -> Option<usize> {
+        *self.fuel.borrow()
+    }
+
+    pub fn fuel_consumed(&self) -> usize {
+        *self.fuel_consumed.borrow()
+    }
+
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    pub fn define_native<F>(&self, name: &str, arity: usize, func: F)
+    where
+        F: Fn(&[Object]) -> Result<Object, String> + 'static,
+    {
+        // expose a host closure to scripts as a global function
+        let native = NativeFunction::new(name, arity, func);
+        self.globals
+            .borrow_mut()
+            .define(name, Object::Native(Rc::new(LoxNative { func: Rc::new(native) })));
+    }
+
+    pub fn define_builtin<F>(&self, name: &str, arity: usize, func: F)
+    where
+        F: Fn(&[Object]) -> Result<Object, ErrorMessage> + 'static,
+    {
+        // like define_native, for the built-in library whose errors have codes
+        let native = NativeFunction::builtin(name, arity, func);
+        self.globals
+            .borrow_mut()
+            .define(name, Object::Native(Rc::new(LoxNative { func: Rc::new(native) })));
+    }
+
+    pub fn define_class(&self, class: LoxClass) {
+        // expose a host-defined class to scripts as a global
+        let name = class.name().to_string();
+        self.globals
+            .borrow_mut()
+            .define(&name, Object::Class(Rc::new(class)));
+    }
+
+    pub fn set_output<W: Write + 'static>(&self, writer: W) {
+        // redirect everything printed by scripts to the given writer
+        self.output.replace(Box::new(writer));
+    }
+
+    pub fn set_output_callback<F: FnMut(&str) + 'static>(&self, callback: F) {
+        self.set_output(CallbackWriter { callback });
     }
 
     fn evaluate(&self, expr: Rc<Expr>) -> Result<Object, LoxResult> {
+        self.step(expr.span())?;
         expr.accept(expr.clone(), self)
     }
 
     fn execute(&self, stmt: Rc<Stmt>) -> Result<(), LoxResult> {
+        self.step(stmt.span())?;
         stmt.accept(stmt.clone(), self)
     }
 
+    fn step(&self, span: Span) -> Result<(), LoxResult> {
+        if self.cancel.borrow().is_cancelled() {
+            return Err(LoxResult::Interrupted);
+        }
+        if let Some(fuel) = self.fuel.borrow_mut().as_mut() {
+            if *fuel == 0 {
+                return Err(LoxResult::fuel_exhausted(span));
+            }
+            *fuel -= 1;
+        }
+        *self.fuel_consumed.borrow_mut() += 1;
+        // calls stop at max_stack; the slack is for nesting within the last call
+        let max_stack = *self.max_stack.borrow();
+        if self.stack_used() >= max_stack + max_stack / 4 {
+            return Err(LoxResult::too_much_nesting(DiagnosticKind::Runtime, span));
+        }
+        Ok(())
+    }
+
     pub fn resolve(&self, expr: Rc<Expr>, depth: usize) -> Result<(), LoxResult> {
         self.locals.borrow_mut().insert(expr, depth);
         Ok(())
@@ -375,28 +676,74 @@ impl Interpreter {
             (Object::Num(x), Object::Num(y)) => Ok(x == y),
             (Object::Str(x), Object::Str(y)) => Ok(x == y),
             (Object::Bool(x), Object::Bool(y)) => Ok(x == y),
-            _ => Err(Object::ErrorMessage(
-                    "Cannot compare objects of different types.".to_string(),
-            )),
+            (Object::List(x), Object::List(y)) => Ok(Rc::ptr_eq(x, y)),
+            (Object::Map(x), Object::Map(y)) => Ok(Rc::ptr_eq(x, y)),
+            _ => Err(Object::ErrorMessage(ErrorMessage::new(
+                    "L0311",
+                    "Cannot compare objects of different types.",
+            ))),
         }
     }
 
     fn look_up_variable(&self, name: &Token, expr: Rc<Expr>) -> Result<Object, LoxResult> {
         if let Some(distance) = self.locals.borrow().get(&expr) {
-            self.environment.borrow().borrow().get_at(*distance, name.as_string())
+            self.environment
+                .borrow()
+                .borrow()
+                .get_at(*distance, name.as_string())
+                .ok_or_else(|| {
+                    LoxResult::runtime_error(name, "L0303", &format!("Undefined variable '{}'.", name.as_string()))
+                })
         } else { 
             self.globals.borrow().get(name)
         }
     }
 
-    pub fn interpret(&self, statements: &[Rc<Stmt>]) -> bool {
-        let mut success = true;
-        for stmt in statements {
-            if self.execute(stmt.clone()).is_err() {
-                success = false;
-                break;
+    fn with_backtrace(&self, error: LoxResult) -> LoxResult {
+        // record the active call frames the first time a runtime error unwinds through one
+        match error {
+            LoxResult::LoxRuntimeError { token, code, message, trace } if trace.is_empty() => {
+                let mut trace = Vec::new();
+                let mut line = token.line;
+                for frame in self.frames.borrow().iter().rev() {
+                    trace.push(StackFrame { function: frame.function.clone(), line });
+                    line = frame.call_line;
+                }
+                trace.push(StackFrame { function: "<script>".to_string(), line });
+                LoxResult::LoxRuntimeError { token, code, message, trace }
             }
+            e => e,
         }
-        success
+    }
+
+    pub fn interpret(&self, statements: &[Rc<Stmt>]) -> Result<(), LoxResult> {
+        self.stack_base.replace(Some(stack_address()));
+        let result = statements
+            .iter()
+            .try_for_each(|stmt| self.execute(stmt.clone()).map_err(|e| self.with_backtrace(e)));
+        self.stack_base.replace(None);
+        if let Err(LoxResult::Interrupted) = &result {
+            // the cancel has been served; it must not stop the next run too
+            self.cancel.borrow().reset();
+        }
+        if let Err(e) = &result {
+            self.diagnostics.report(e);
+        }
+        result
+    }
+}
+
+struct CallbackWriter<F: FnMut(&str)> {
+    callback: F,
+}
+
+impl<F: FnMut(&str)> Write for CallbackWriter<F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (self.callback)(&String::from_utf8_lossy(buf));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
     }
 }
@@ -0,0 +1,40 @@
+// LoxResult carries tokens and doubles as control flow for return/break
+#![allow(clippy::result_large_err)]
+
+pub mod builtins;
+pub mod callable;
+pub mod cancel;
+pub mod diagnostic;
+pub mod environment;
+pub mod error;
+pub mod error_code;
+pub mod expr;
+pub mod interpreter;
+pub mod lox_class;
+pub mod lox_function;
+pub mod lox_instance;
+pub mod lox_map;
+pub mod native_functions;
+pub mod parser;
+pub mod renderer;
+pub mod resolver;
+pub mod scanner;
+pub mod stmt;
+pub mod token;
+pub mod token_type;
+mod lox;
+mod stack;
+
+pub use cancel::CancelHandle;
+pub use diagnostic::{Diagnostic, Diagnostics};
+pub use error::LoxResult;
+pub use error_code::ErrorCode;
+pub use interpreter::Interpreter;
+pub use lox::{Lox, RunError, WarningMode};
+pub use lox_class::LoxClass;
+pub use lox_instance::LoxInstance;
+pub use parser::Parser;
+pub use renderer::{ColorMode, Renderer};
+pub use resolver::Resolver;
+pub use scanner::Scanner;
+pub use token::Object;
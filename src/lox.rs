@@ -0,0 +1,167 @@
+#[cfg(unix)]
+use crate::cancel::interrupt;
+use crate::diagnostic::*;
+use crate::error::*;
+use crate::interpreter::*;
+use crate::parser::*;
+use crate::renderer::*;
+use crate::resolver::*;
+use crate::scanner::*;
+use crate::stmt::*;
+use std::io::{self, stdout, BufRead, Write};
+use std::rc::Rc;
+
+pub struct Lox {
+    interpreter: Interpreter,
+    warnings: WarningMode,
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum WarningMode {
+    Warn,
+    Allow,
+    Deny,
+}
+
+#[derive(Debug)]
+pub enum RunError {
+    // scanning, parsing or resolution failed; nothing was executed
+    Compile,
+    Runtime,
+    Interrupted,
+    Io(io::Error),
+}
+
+impl RunError {
+    pub fn exit_code(&self) -> i32 {
+        // sysexits.h codes, as used by the reference jlox and clox
+        match self {
+            RunError::Compile => 65,
+            RunError::Runtime => 70,
+            RunError::Interrupted => 130,
+            RunError::Io(_) => 74,
+        }
+    }
+}
+
+impl Default for Lox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Lox {
+    pub fn new() -> Lox {
+        Lox {
+            interpreter: Interpreter::new(),
+            warnings: WarningMode::Warn,
+        }
+    }
+
+    pub fn set_warning_mode(&mut self, mode: WarningMode) {
+        // Allow drops warnings, Deny reports them as errors and stops the run
+        self.warnings = mode;
+    }
+
+    pub fn interpreter(&self) -> &Interpreter {
+        &self.interpreter
+    }
+
+    pub fn diagnostics(&self) -> &Diagnostics {
+        self.interpreter.diagnostics()
+    }
+
+    pub fn run_file(&mut self, path: &str) -> Result<(), RunError> {
+        let buf = std::fs::read_to_string(path).map_err(|e| {
+            self.diagnostics()
+                .report(&LoxResult::system_error("L0502", &format!("Could not read '{path}': {e}.")));
+            RunError::Io(e)
+        })?;
+        self.run(&buf)
+    }
+
+    pub fn run_prompt(&mut self) {
+        #[cfg(unix)]
+        self.interpreter.set_cancel_handle(interrupt::handle());
+
+        let stdin = io::stdin();
+        print!("> ");
+        let _ = stdout().flush();
+        for line in stdin.lock().lines() {
+            if let Ok(line) = line {
+                if line.is_empty() {
+                    break;
+                }
+                #[cfg(unix)]
+                let result = {
+                    // a Ctrl-C that came too late to stop the previous line is stale
+                    self.interpreter.cancel_handle().reset();
+                    interrupt::catch(|| self.run(&line))
+                };
+                #[cfg(not(unix))]
+                let result = self.run(&line);
+                if let Err(RunError::Interrupted) = result {
+                    eprintln!("Interrupted.");
+                }
+                let renderer = Renderer::new("<stdin>", &line, ColorMode::detect());
+                for diagnostic in self.diagnostics().take() {
+                    eprintln!("{}", renderer.render(&diagnostic));
+                }
+            } else {
+                break;
+            }
+            print!("> ");
+            let _ = stdout().flush();
+        }
+    }
+
+    pub fn run(&mut self, source: &str) -> Result<(), RunError> {
+        let statements = self.compile(source)?;
+        self.execute(&statements)
+    }
+
+    pub fn compile(&mut self, source: &str) -> Result<Rc<Vec<Rc<Stmt>>>, RunError> {
+        // scan, parse and resolve without running anything, so hosts can show
+        // the front end's diagnostics before the script starts
+        let diagnostics = self.diagnostics().clone();
+        diagnostics.start_run();
+        let mut scanner = Scanner::new(source.to_string(), &diagnostics);
+        let tokens = scanner.scan_tokens().map_err(|_| RunError::Compile)?;
+        let mut parser = Parser::new(tokens, &diagnostics);
+        parser.set_max_stack(self.interpreter.max_stack());
+        let statements = parser.parse().map_err(|_| RunError::Compile)?;
+
+        // resolve what did parse so all front-end errors are reported together
+        let resolver = Resolver::new(&self.interpreter);
+        resolver.set_lints(parser.success());
+        let s = Rc::new(statements);
+        resolver.resolve(Rc::clone(&s)).map_err(|_| RunError::Compile)?;
+        let denied = match self.warnings {
+            WarningMode::Warn => false,
+            WarningMode::Allow => {
+                diagnostics.allow_warnings();
+                false
+            }
+            WarningMode::Deny => {
+                let warned = diagnostics.has_warnings();
+                diagnostics.deny_warnings();
+                warned
+            }
+        };
+        if !parser.success() || !resolver.success() || denied {
+            return Err(RunError::Compile);
+        }
+        Ok(s)
+    }
+
+    pub fn execute(&mut self, statements: &[Rc<Stmt>]) -> Result<(), RunError> {
+        // statements must come from compile() on this Lox, which resolved them
+        match self.interpreter.interpret(statements) {
+            Ok(()) => Ok(()),
+            Err(LoxResult::Interrupted) => Err(RunError::Interrupted),
+            // the host failed underneath the script, e.g. print couldn't write
+            Err(LoxResult::LoxSystemError { message, .. }) => Err(RunError::Io(io::Error::other(message))),
+            Err(_) => Err(RunError::Runtime),
+        }
+    }
+}
@@ -3,6 +3,7 @@ use crate::error::*;
 use crate::callable::*;
 use crate::token::*;
 use crate::lox_instance::*;
+use crate::native_functions::*;
 use std::rc::Rc;
 use std::fmt;
 use std::collections::HashMap;
@@ -11,22 +12,58 @@ use std::collections::HashMap;
 pub struct LoxClass {
     name: String,
     superclass: Option<Rc<LoxClass>>,
-    methods: HashMap<String, Object>
+    methods: HashMap<String, Object>,
+    native_methods: HashMap<String, Rc<NativeMethod>>,
 }
 
 impl LoxClass {
     pub fn new(name: &str, superclass: Option<Rc<LoxClass>>, methods: HashMap<String, Object>) -> LoxClass {
-        LoxClass { name: name.to_owned(), superclass, methods }
+        LoxClass { name: name.to_owned(), superclass, methods, native_methods: HashMap::new() }
+    }
+
+    pub fn native(name: &str) -> LoxClass {
+        // class whose methods are implemented by the host
+        LoxClass::new(name, None, HashMap::new())
+    }
+
+    pub fn define_method<F>(&mut self, name: &str, arity: usize, func: F)
+    where
+        F: Fn(&Rc<LoxInstance>, &[Object]) -> Result<Object, String> + 'static,
+    {
+        self.native_methods.insert(name.to_string(), Rc::new(NativeMethod::new(name, arity, func)));
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
     }
 
     pub fn instantiate(&self, interpreter: &Interpreter, arguments: Vec<Object>, klass: Rc<LoxClass>) -> Result<Object, LoxResult> {
-        let instance = Object::Instance(Rc::new(LoxInstance::new(klass)));
-        if let Some(Object::Function(initializer)) = self.find_method("init") {
-            if let Object::Function(init) = initializer.bind(&instance) {
+        let instance = Rc::new(LoxInstance::new(klass));
+        match self.bind_method("init", &instance) {
+            Some(Object::Function(init)) => {
                 init.call(interpreter, arguments, None)?;
             }
+            Some(Object::Native(init)) => {
+                // let the call site report errors raised by a host initializer
+                if let Object::ErrorMessage(message) = init.func.call(interpreter, arguments, None)? {
+                    return Ok(Object::ErrorMessage(message));
+                }
+            }
+            _ => {}
+        }
+        Ok(Object::Instance(instance))
+    }
+
+    pub fn bind_method(&self, name: &str, instance: &Rc<LoxInstance>) -> Option<Object> {
+        if let Some(Object::Function(method)) = self.methods.get(name) {
+            Some(method.bind(&Object::Instance(Rc::clone(instance))))
+        } else if let Some(method) = self.native_methods.get(name) {
+            Some(NativeMethod::bind(method, instance))
+        } else if let Some(superclass) = &self.superclass {
+            superclass.bind_method(name, instance)
+        } else {
+            None
         }
-        Ok(instance)
     }
 
     pub fn find_method(&self, name: &str) -> Option<Object> {
@@ -42,12 +79,20 @@ impl LoxClass {
 
 impl LoxCallable for LoxClass {
     fn call(&self, interpreter: &Interpreter, arguments: Vec<Object>, klass: Option<Rc<LoxClass>>) -> Result<Object, LoxResult> {
-        self.instantiate(interpreter, arguments, klass.unwrap())
+        match klass {
+            Some(klass) => self.instantiate(interpreter, arguments, klass),
+            // the call site reports this like any other failed call
+            None => Ok(Object::ErrorMessage(ErrorMessage::new("L0320", &format!("Can't instantiate class '{}'.", self.name)))),
+        }
     }
 
     fn arity(&self) -> usize {
-        if let Some(Object::Function(initializer)) = self.find_method("init") {
+        if let Some(Object::Function(initializer)) = self.methods.get("init") {
             initializer.arity()
+        } else if let Some(initializer) = self.native_methods.get("init") {
+            initializer.arity()
+        } else if let Some(superclass) = &self.superclass {
+            superclass.arity()
         } else {
             0
         }
@@ -59,6 +104,7 @@ impl fmt::Display for LoxClass {
         let methods = self
             .methods
             .keys()
+            .chain(self.native_methods.keys())
             .cloned()
             .collect::<Vec<String>>()
             .join(", ");
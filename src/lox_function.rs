@@ -4,7 +4,9 @@ use crate::token::*;
 use crate::callable::*;
 use crate::error::*;
 use crate::lox_class::*;
+use crate::expr::*;
 use crate::stmt::*;
+use crate::token_type::*;
 use std::rc::Rc;
 use std::fmt;
 use std::cell::RefCell;
@@ -55,6 +57,30 @@ impl LoxFunction {
         } 
     }
 
+    pub fn lambda(expr: &LambdaExpr, closure: &Rc<RefCell<Environment>>) -> LoxFunction {
+        // anonymous functions are reported as '<lambda>' in backtraces
+        let name = Token::new(TokenType::Identifier, "<lambda>".to_string(), None, expr.keyword.span);
+        LoxFunction {
+            name,
+            params: Rc::clone(&expr.params),
+            body: Rc::clone(&expr.body),
+            closure: Rc::clone(closure),
+            is_initializer: false,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_string()
+    }
+
+    fn this(&self) -> Result<Object, LoxResult> {
+        // initializers always return the instance they were bound to
+        self.closure
+            .borrow()
+            .get_at(0, "this")
+            .ok_or_else(|| LoxResult::runtime_error(&self.name, "L0320", "Initializer is not bound to an instance."))
+    }
+
     pub fn bind(&self, instance: &Object) -> Object {
         let environment = RefCell::new(Environment::new_with_enclosing(Rc::clone(&self.closure)));
         environment.borrow_mut().define("this", instance.clone());
@@ -80,14 +106,14 @@ impl LoxCallable for LoxFunction {
         match interpreter.execute_block(&self.body, environment) {
             Err(LoxResult::Return { value }) => { 
                 if self.is_initializer {
-                    self.closure.borrow().get_at(0, "this") 
+                    self.this()
                 } else {
                     Ok(value)
                 }
             }
             Err(e) => Err(e),
             Ok(_) => if self.is_initializer { 
-                self.closure.borrow().get_at(0, "this") 
+                self.this()
             } else { 
                 Ok(Object::Nil) 
             },
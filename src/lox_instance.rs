@@ -1,39 +1,60 @@
+use std::any::Any;
 use std::fmt;
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Ref, RefCell, RefMut};
 use std::collections::hash_map::*;
 use crate::lox_class::*;
 use crate::token::*;
 use crate::error::*;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug)]
 pub struct LoxInstance {
     pub klass: Rc<LoxClass>,
     fields: RefCell<HashMap<String, Object>>,
+    payload: RefCell<Option<Box<dyn Any>>>,
+}
+
+impl PartialEq for LoxInstance {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
 }
 
 impl LoxInstance {
     pub fn new(klass: Rc<LoxClass>) -> LoxInstance {
-        LoxInstance { klass: Rc::clone(&klass), fields: RefCell::new(HashMap::new()) }
+        LoxInstance { 
+            klass: Rc::clone(&klass), 
+            fields: RefCell::new(HashMap::new()),
+            payload: RefCell::new(None),
+        }
     }
 
     pub fn get(&self, name: &Token, this: &Rc<LoxInstance>) -> Result<Object, LoxResult> {
         if let Entry::Occupied(o) = self.fields.borrow_mut().entry(name.as_string().into()) {
             Ok(o.get().clone())
-        } else if let Some(method) = self.klass.find_method(name.as_string()) { 
-            if let Object::Function(func) = method {
-                return Ok(func.bind(&Object::Instance(Rc::clone(this))));
-            } else {
-                panic!("Tried to bind 'this' incorrectly.")
-            }
+        } else if let Some(method) = self.klass.bind_method(name.as_string(), this) { 
+            Ok(method)
         } else {
-            Err(LoxResult::runtime_error(name, &format!("Undefined property '{}'.", name.as_string())))
+            Err(LoxResult::runtime_error(name, "L0304", &format!("Undefined property '{}'.", name.as_string())))
         }
     }
 
     pub fn set(&self, name: &Token, value: Object) {
         self.fields.borrow_mut().insert(name.as_string().into(), value);
     }
+
+    pub fn set_payload<T: Any>(&self, value: T) {
+        // opaque host state carried by instances of native classes
+        self.payload.replace(Some(Box::new(value)));
+    }
+
+    pub fn payload<T: Any>(&self) -> Option<Ref<'_, T>> {
+        Ref::filter_map(self.payload.borrow(), |p| p.as_ref().and_then(|p| p.downcast_ref::<T>())).ok()
+    }
+
+    pub fn payload_mut<T: Any>(&self) -> Option<RefMut<'_, T>> {
+        RefMut::filter_map(self.payload.borrow_mut(), |p| p.as_mut().and_then(|p| p.downcast_mut::<T>())).ok()
+    }
 }
 
 impl fmt::Display for LoxInstance {
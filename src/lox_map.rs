@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use crate::error::*;
+use crate::token::*;
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum MapKey {
+    Nil,
+    Bool(bool),
+    Num(u64),
+    Str(String),
+}
+
+impl MapKey {
+    fn from(key: &Object) -> Result<MapKey, ErrorMessage> {
+        match key {
+            Object::Nil => Ok(MapKey::Nil),
+            Object::Bool(b) => Ok(MapKey::Bool(*b)),
+            // -0 and 0 compare equal, so they must hash the same
+            Object::Num(n) if *n == 0.0 => Ok(MapKey::Num(0.0f64.to_bits())),
+            Object::Num(n) => Ok(MapKey::Num(n.to_bits())),
+            Object::Str(s) => Ok(MapKey::Str(s.clone())),
+            _ => Err(ErrorMessage::new("L0317", "Map keys must be strings, numbers, booleans or nil.")),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct LoxMap {
+    // entries in insertion order, plus where each key lives in that order
+    entries: Vec<(Object, Object)>,
+    index: HashMap<MapKey, usize>,
+}
+
+impl LoxMap {
+    pub fn new() -> LoxMap {
+        LoxMap::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[(Object, Object)] {
+        &self.entries
+    }
+
+    pub fn get(&self, key: &Object) -> Result<Option<Object>, ErrorMessage> {
+        let key = MapKey::from(key)?;
+        Ok(self.index.get(&key).map(|&i| self.entries[i].1.clone()))
+    }
+
+    pub fn contains(&self, key: &Object) -> Result<bool, ErrorMessage> {
+        Ok(self.index.contains_key(&MapKey::from(key)?))
+    }
+
+    pub fn insert(&mut self, key: Object, value: Object) -> Result<(), ErrorMessage> {
+        // overwriting a key keeps its original position
+        match self.index.get(&MapKey::from(&key)?) {
+            Some(&i) => self.entries[i].1 = value,
+            None => {
+                self.index.insert(MapKey::from(&key)?, self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, key: &Object) -> Result<Option<Object>, ErrorMessage> {
+        let Some(i) = self.index.remove(&MapKey::from(key)?) else {
+            return Ok(None);
+        };
+        let (_, value) = self.entries.remove(i);
+        // everything after the removed entry moved down by one
+        for position in self.index.values_mut() {
+            if *position > i {
+                *position -= 1;
+            }
+        }
+        Ok(Some(value))
+    }
+}
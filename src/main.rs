@@ -1,95 +1,85 @@
+use lox_ast::{ColorMode, ErrorCode, Lox, Renderer, WarningMode};
 use std::env::args;
-use std::io::{self, stdout, BufRead, Write};
-use std::rc::Rc;
 
-mod environment;
-mod error;
-mod expr;
-mod interpreter;
-mod parser;
-mod scanner;
-mod stmt;
-mod token;
-mod token_type;
-mod callable;
-mod native_functions;
-mod lox_instance;
-mod lox_function;
-mod lox_class;
-mod resolver;
+const USAGE: &str = "Usage: rlox [--no-warnings | --warnings-as-errors] [script]\n       rlox --explain CODE";
 
-use error::*;
-use interpreter::*;
-use parser::*;
-use scanner::*;
-use resolver::*;
+// The interpreter runs on its own thread so the stack it may use is known
+// rather than whatever the platform gives the main thread.
+const STACK_SIZE: usize = 64 * 1024 * 1024;
 
 pub fn main() {
-    let args: Vec<String> = args().collect();
+    let cli = std::thread::Builder::new().stack_size(STACK_SIZE).spawn(cli).unwrap();
+    if cli.join().is_err() {
+        std::process::exit(70);
+    }
+}
+
+fn cli() {
+    let args: Vec<String> = args().skip(1).collect();
     let mut lox = Lox::new();
+    // keep the same share of the stack for the host that a default thread gets
+    lox.interpreter().set_max_stack(STACK_SIZE / 2);
 
-    match args.len() {
-        1 => lox.run_prompt(),
-        2 => lox.run_file(&args[1]).expect("Could not run file"),
-        _ => {
-            println!("Usage: rlox [script]");
-            std::process::exit(64);
+    if args.len() == 2 && args[0] == "--explain" {
+        return explain(&args[1]);
+    }
+
+    let mut scripts = Vec::new();
+    for arg in &args {
+        match arg.as_str() {
+            "--no-warnings" => lox.set_warning_mode(WarningMode::Allow),
+            "--warnings-as-errors" => lox.set_warning_mode(WarningMode::Deny),
+            flag if flag.starts_with("--") => usage(),
+            script => scripts.push(script),
         }
     }
+
+    match scripts[..] {
+        [] => lox.run_prompt(),
+        [path] => run_file(&mut lox, path),
+        _ => usage(),
+    }
 }
 
-struct Lox {
-    interpreter: Interpreter,
+fn usage() {
+    println!("{USAGE}");
+    std::process::exit(64);
 }
 
-impl Lox {
-    pub fn new() -> Lox {
-        Lox {
-            interpreter: Interpreter::new(),
+fn explain(code: &str) {
+    match ErrorCode::find(code) {
+        Some(error) => println!("{} ({}): {}\n\n{}", error.code, error.kind, error.summary, error.explanation),
+        None => {
+            eprintln!("Unknown error code '{code}'.");
+            std::process::exit(64);
         }
     }
+}
 
-    pub fn run_file(&mut self, path: &str) -> io::Result<()> {
-        let buf = std::fs::read_to_string(path)?;
-        if self.run(buf).is_err() {
-            std::process::exit(65);
+fn run_file(lox: &mut Lox, path: &str) {
+    let source = match std::fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("System Error: Could not read '{path}': {e}.");
+            std::process::exit(74);
         }
+    };
 
-        Ok(())
-    }
-
-    pub fn run_prompt(&mut self) {
-        let stdin = io::stdin();
-        print!("> ");
-        let _ = stdout().flush();
-        for line in stdin.lock().lines() {
-            if let Ok(line) = line {
-                if line.is_empty() {
-                    break;
-                }
-                let _ = self.run(line);
-            } else {
-                break;
-            }
-            print!("> ");
-            let _ = stdout().flush();
+    let renderer = Renderer::new(path, &source, ColorMode::detect());
+    let report = |lox: &Lox| {
+        for diagnostic in lox.diagnostics().take() {
+            eprintln!("{}", renderer.render(&diagnostic));
         }
-    }
+    };
 
-    fn run(&mut self, source: String) -> Result<(), LoxResult> {
-        let mut scanner = Scanner::new(source);
-        let tokens = scanner.scan_tokens()?;
-        let mut parser = Parser::new(tokens);
-        let statements = parser.parse()?;
+    // warnings come before the script's output, not after it has finished
+    let result = lox.compile(&source).and_then(|statements| {
+        report(lox);
+        lox.execute(&statements)
+    });
+    report(lox);
 
-        if parser.success() {
-            let resolver = Resolver::new(&self.interpreter);
-            let s = Rc::new(statements);
-            resolver.resolve(Rc::clone(&s))?;
-            if resolver.success() {
-                self.interpreter.interpret(&Rc::clone(&s));
-            }
-        }
-        Ok(())
+    if let Err(e) = result {
+        std::process::exit(e.exit_code());
     }
 }
@@ -5,6 +5,7 @@ use crate::error::*;
 use crate::token::*;
 use crate::callable::*;
 use crate::lox_class::*;
+use crate::lox_instance::*;
 use std::rc::Rc;
 
 #[derive(Clone)]
@@ -37,6 +38,7 @@ impl LoxCallable for NativeClock {
         match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
             Ok(n) => Ok(Object::Num(n.as_millis() as f64)),
             Err(e) => Err(LoxResult::system_error(
+                "L0503",
                 &format!("Clock returned invalid duration: {:?}.", e.duration())
             ))
         }
@@ -46,3 +48,109 @@ impl LoxCallable for NativeClock {
         0
     }
 }
+
+type NativeFn = dyn Fn(&[Object]) -> Result<Object, ErrorMessage>;
+
+pub struct NativeFunction {
+    name: String,
+    arity: usize,
+    func: Box<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn new<F>(name: &str, arity: usize, func: F) -> NativeFunction
+    where
+        F: Fn(&[Object]) -> Result<Object, String> + 'static,
+    {
+        // errors from host functions have no code
+        NativeFunction::builtin(name, arity, move |args| func(args).map_err(ErrorMessage::host))
+    }
+
+    pub fn builtin<F>(name: &str, arity: usize, func: F) -> NativeFunction
+    where
+        F: Fn(&[Object]) -> Result<Object, ErrorMessage> + 'static,
+    {
+        NativeFunction { name: name.to_string(), arity, func: Box::new(func) }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl LoxCallable for NativeFunction {
+    fn call(&self, _: &Interpreter, arguments: Vec<Object>, _: Option<Rc<LoxClass>>) -> Result<Object, LoxResult> {
+        // host errors are reported at the call site by the interpreter
+        match (self.func)(&arguments) {
+            Ok(value) => Ok(value),
+            Err(error) => Ok(Object::ErrorMessage(error)),
+        }
+    }
+
+    fn arity(&self) -> usize {
+        self.arity
+    }
+}
+
+type NativeMethodFn = dyn Fn(&Rc<LoxInstance>, &[Object]) -> Result<Object, String>;
+
+pub struct NativeMethod {
+    name: String,
+    arity: usize,
+    func: Box<NativeMethodFn>,
+}
+
+impl fmt::Debug for NativeMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<Native Method {}>", self.name)
+    }
+}
+
+impl PartialEq for NativeMethod {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl NativeMethod {
+    pub fn new<F>(name: &str, arity: usize, func: F) -> NativeMethod
+    where
+        F: Fn(&Rc<LoxInstance>, &[Object]) -> Result<Object, String> + 'static,
+    {
+        NativeMethod { name: name.to_string(), arity, func: Box::new(func) }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    pub fn bind(method: &Rc<NativeMethod>, instance: &Rc<LoxInstance>) -> Object {
+        let bound = BoundNativeMethod {
+            method: Rc::clone(method),
+            this: Rc::clone(instance),
+        };
+        Object::Native(Rc::new(LoxNative { func: Rc::new(bound) }))
+    }
+}
+
+struct BoundNativeMethod {
+    method: Rc<NativeMethod>,
+    this: Rc<LoxInstance>,
+}
+
+impl LoxCallable for BoundNativeMethod {
+    fn call(&self, _: &Interpreter, arguments: Vec<Object>, _: Option<Rc<LoxClass>>) -> Result<Object, LoxResult> {
+        match (self.method.func)(&self.this, &arguments) {
+            Ok(value) => Ok(value),
+            Err(message) => Ok(Object::ErrorMessage(ErrorMessage::host(message))),
+        }
+    }
+
+    fn arity(&self) -> usize {
+        self.method.arity
+    }
+}
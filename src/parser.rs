@@ -1,30 +1,58 @@
+use crate::diagnostic::*;
 use crate::error::*;
 use crate::expr::*;
+use crate::interpreter::DEFAULT_MAX_STACK;
+use crate::stack::*;
 use crate::stmt::*;
 use crate::token::*;
 use crate::token_type::*;
 
 use std::rc::Rc;
 
+// How deeply statements and expressions may nest. Everything after the parser
+// walks the tree recursively, so this also bounds the resolver and interpreter.
+pub const MAX_NESTING: usize = 256;
+
 pub struct Parser<'a> {
     tokens: &'a Vec<Token>,
     current: usize,
     had_error: bool,
+    block_depth: usize,
+    depth: usize,
+    too_deep: bool,
+    max_stack: usize,
+    stack_base: Option<usize>,
+    diagnostics: Diagnostics,
 }
 
 impl Parser<'_> {
-    pub fn new(tokens: &Vec<Token>) -> Parser {
+    pub fn new<'a>(tokens: &'a Vec<Token>, diagnostics: &Diagnostics) -> Parser<'a> {
         Parser {
             tokens,
             current: 0,
             had_error: false,
+            block_depth: 0,
+            depth: 0,
+            too_deep: false,
+            max_stack: DEFAULT_MAX_STACK,
+            stack_base: None,
+            diagnostics: diagnostics.clone(),
         }
     }
 
+    pub fn set_max_stack(&mut self, bytes: usize) {
+        // debug builds can run out of stack well before MAX_NESTING
+        self.max_stack = bytes;
+    }
+
     pub fn parse(&mut self) -> Result<Vec<Rc<Stmt>>, LoxResult> {
+        self.stack_base = Some(stack_address());
         let mut statements = Vec::new();
-        while !self.is_at_end() {
-            statements.push(self.declaration()?);
+        while !self.is_at_end() && !self.too_deep {
+            // errors are already reported; keep going to find the rest
+            if let Ok(statement) = self.declaration() {
+                statements.push(statement);
+            }
         }
         Ok(statements)
     }
@@ -34,21 +62,48 @@ impl Parser<'_> {
     }
 
     fn expression(&mut self) -> Result<Expr, LoxResult> {
-        self.assignment()
+        self.nested(|p| p.assignment())
+    }
+
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T, LoxResult>) -> Result<T, LoxResult> {
+        // parse one level deeper, restoring the depth however parsing ends
+        let depth = self.depth;
+        let result = self.deeper().and_then(|_| parse(self));
+        self.depth = depth;
+        result
+    }
+
+    fn deeper(&mut self) -> Result<(), LoxResult> {
+        // chains like a + b + c nest one level per operator; callers restore the depth
+        self.depth += 1;
+        if self.depth > MAX_NESTING || stack_used(self.stack_base) > self.max_stack {
+            // there is no recovering inside the nesting, so parsing stops here
+            self.too_deep = true;
+            let peek = self.peek().duplicate();
+            return Err(self.error(&peek, "L0111", "Too much nesting."));
+        }
+        Ok(())
     }
 
     fn declaration(&mut self) -> Result<Rc<Stmt>, LoxResult> {
+        let start = self.current;
         let result = if self.is_match(&[TokenType::Class]) {
             self.class_declaration() 
         } else if self.is_match(&[TokenType::Var]) {
             self.var_declaration()
-        } else if self.is_match(&[TokenType::Fun]) { 
+        } else if self.check(TokenType::Fun) && self.check_next(TokenType::Identifier) {
+            // a bare 'fun (' starts a lambda expression instead
+            self.advance();
             self.function("function")
         } else {
-            self.statement()
+            self.nested(|p| p.statement())
         };
 
-        if result.is_err() {
+        if result.is_err() && !self.too_deep {
+            // always make progress, even if the first token was the problem
+            if self.current == start {
+                self.advance();
+            }
             self.synchronize();
         }
 
@@ -56,11 +111,13 @@ impl Parser<'_> {
     }
 
     fn class_declaration(&mut self) -> Result<Rc<Stmt>, LoxResult> {
+        let start = self.previous().span;
         let name = self.consume(TokenType::Identifier, "Expect class name.")?;
         let superclass = if self.is_match(&[TokenType::Less]) {
-            self.consume(TokenType::Identifier, "Expect superclass name.")?;
+            let name = self.consume(TokenType::Identifier, "Expect superclass name.")?;
             Some(Rc::new(Expr::Variable(Rc::new(VariableExpr {
-                name: self.previous().duplicate(),
+                span: name.span,
+                name,
             }))))
         } else {
             None
@@ -77,7 +134,8 @@ impl Parser<'_> {
         Ok(Rc::new(Stmt::Class(Rc::new(ClassStmt { 
             name, 
             superclass,
-            methods: Rc::new(methods) 
+            methods: Rc::new(methods),
+            span: self.span_from(start),
         }))))
     }
 
@@ -85,6 +143,9 @@ impl Parser<'_> {
         if self.is_match(&[TokenType::Break]) {
             return Ok(Rc::new(self.break_statement()?));
         }
+        if self.is_match(&[TokenType::Continue]) {
+            return Ok(Rc::new(self.continue_statement()?));
+        }
         if self.is_match(&[TokenType::For]) {
             return self.for_statement();
         }
@@ -101,8 +162,11 @@ impl Parser<'_> {
             return Ok(Rc::new(self.while_statement()?));
         }
         if self.is_match(&[TokenType::LeftBrace]) {
+            let start = self.previous().span;
+            let statements = Rc::new(self.block()?);
             return Ok(Rc::new(Stmt::Block(Rc::new(BlockStmt {
-                statements: Rc::new(self.block()?),
+                statements,
+                span: self.span_from(start),
             }))));
         }
         self.expression_statement()
@@ -114,10 +178,22 @@ impl Parser<'_> {
             TokenType::Semicolon,
             "Expect ';' after 'break'.",
         )?;
-        Ok(Stmt::Break(Rc::new(BreakStmt { token })))
+        let span = self.span_from(token.span);
+        Ok(Stmt::Break(Rc::new(BreakStmt { token, span })))
+    }
+
+    fn continue_statement(&mut self) -> Result<Stmt, LoxResult> {
+        let token = self.previous().duplicate();
+        self.consume(
+            TokenType::Semicolon,
+            "Expect ';' after 'continue'.",
+        )?;
+        let span = self.span_from(token.span);
+        Ok(Stmt::Continue(Rc::new(ContinueStmt { token, span })))
     }
 
     fn for_statement(&mut self) -> Result<Rc<Stmt>, LoxResult> {
+        let start = self.previous().span;
         self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
         let initializer = if self.is_match(&[TokenType::Semicolon]) {
             None
@@ -149,28 +225,27 @@ impl Parser<'_> {
             "Expect ')' after for clauses.",
         )?;
 
-        let mut body = self.statement()?;
-
-        if let Some(incr) = increment {
-            body = Rc::new(Stmt::Block(Rc::new(BlockStmt {
-                statements: Rc::new(vec![body, Rc::new(Stmt::Expression(Rc::new(ExpressionStmt { expression: Rc::new(incr) })))]),
-            })));
-        }
+        let body = self.nested(|p| p.statement())?;
+        let span = self.span_from(start);
 
-        body = Rc::new(Stmt::While(Rc::new(WhileStmt {
+        let mut body = Rc::new(Stmt::While(Rc::new(WhileStmt {
             condition: if let Some(cond) = condition {
                 Rc::new(cond)
             } else {
                 Rc::new(Expr::Literal(Rc::new(LiteralExpr {
                     value: Some(Object::Bool(true)),
+                    span: start,
                 })))
             },
             body,
+            increment: increment.map(Rc::new),
+            span,
         })));
 
         if let Some(init) = initializer {
             body = Rc::new(Stmt::Block(Rc::new(BlockStmt {
                 statements: Rc::new(vec![init, body]),
+                span,
             })));
         }
 
@@ -178,6 +253,7 @@ impl Parser<'_> {
     }
 
     fn if_statement(&mut self) -> Result<Stmt, LoxResult> {
+        let start = self.previous().span;
         self.consume(TokenType::LeftParen, "Expect '(' after 'if'.")?;
         let condition = Rc::new(self.expression()?);
         self.consume(
@@ -185,9 +261,9 @@ impl Parser<'_> {
             "Expect ')' after condition.",
         )?;
 
-        let then_branch = self.statement()?;
+        let then_branch = self.nested(|p| p.statement())?;
         let else_branch = if self.is_match(&[TokenType::Else]) {
-            Some(self.statement()?)
+            Some(self.nested(|p| p.statement())?)
         } else {
             None
         };
@@ -196,13 +272,15 @@ impl Parser<'_> {
             condition,
             then_branch,
             else_branch,
+            span: self.span_from(start),
         })))
     }
 
     fn print_statement(&mut self) -> Result<Stmt, LoxResult> {
+        let keyword = self.previous().duplicate();
         let value = Rc::new(self.expression()?);
         self.consume(TokenType::Semicolon, "Expect ';' after value.")?;
-        Ok(Stmt::Print(Rc::new(PrintStmt { expression: value })))
+        Ok(Stmt::Print(Rc::new(PrintStmt { span: self.span_from(keyword.span), keyword, expression: value })))
     }
 
     fn return_statement(&mut self) -> Result<Stmt, LoxResult> {
@@ -214,10 +292,12 @@ impl Parser<'_> {
         };
 
         self.consume(TokenType::Semicolon, "Expect ';' after return value.")?;
-        Ok(Stmt::Return(Rc::new(ReturnStmt {keyword, value})))
+        let span = self.span_from(keyword.span);
+        Ok(Stmt::Return(Rc::new(ReturnStmt {keyword, value, span})))
     }
 
     fn var_declaration(&mut self) -> Result<Rc<Stmt>, LoxResult> {
+        let start = self.previous().span;
         let name = self.consume(TokenType::Identifier, "Expect variable name.")?;
         let initializer = if self.is_match(&[TokenType::Equal]) {
             Some(Rc::new(self.expression()?))
@@ -229,10 +309,11 @@ impl Parser<'_> {
             TokenType::Semicolon,
             "Expect ';' after variable declaration.",
         )?;
-        Ok(Rc::new(Stmt::Var(Rc::new(VarStmt { name, initializer }))))
+        Ok(Rc::new(Stmt::Var(Rc::new(VarStmt { name, initializer, span: self.span_from(start) }))))
     }
 
     fn while_statement(&mut self) -> Result<Stmt, LoxResult> {
+        let start = self.previous().span;
         self.consume(
             TokenType::LeftParen,
             "Expect '(' after 'while'.",
@@ -242,46 +323,200 @@ impl Parser<'_> {
             TokenType::RightParen,
             "Expect ')' after condition.",
         )?;
-        let body = self.statement()?;
+        let body = self.nested(|p| p.statement())?;
 
-        Ok(Stmt::While(Rc::new(WhileStmt { condition, body })))
+        Ok(Stmt::While(Rc::new(WhileStmt { condition, body, increment: None, span: self.span_from(start) })))
     }
 
     fn expression_statement(&mut self) -> Result<Rc<Stmt>, LoxResult> {
         let expression = Rc::new(self.expression()?);
         self.consume(TokenType::Semicolon, "Expect ';' after value.")?;
-        Ok(Rc::new(Stmt::Expression(Rc::new(ExpressionStmt { expression }))))
+        let span = self.span_from(expression.span());
+        Ok(Rc::new(Stmt::Expression(Rc::new(ExpressionStmt { expression, span }))))
     }
     
     fn function(&mut self, kind: &str) -> Result<Rc<Stmt>, LoxResult> {
+        let start = self.peek().span;
         let name = self.consume(TokenType::Identifier, &format!("Expect {kind} name."))?;
 
         self.consume(TokenType::LeftParen, &format!("Expect '(' after {kind} name."))?;
+        let params = self.parameters()?;
+
+        self.consume(TokenType::LeftBrace, &format!("Expect '{{' before {kind} body."))?;
+        let body = Rc::new(self.nested(|p| p.block())?);
+        Ok(Rc::new(Stmt::Function(Rc::new(FunctionStmt { name, params: Rc::new(params), body, span: self.span_from(start) }))))
+    }
+
+    fn parameters(&mut self) -> Result<Vec<Token>, LoxResult> {
+        // parameter list after the opening '(', through the closing ')'
         let mut params = Vec::new();
         if !self.check(TokenType::RightParen) {
             params.push(self.consume(TokenType::Identifier, "Expect parameter name.")?);
             while self.is_match(&[TokenType::Comma]) {
-                if params.len() >= 255 && !self.had_error {
+                if params.len() == 255 {
                     let peek = self.peek().duplicate();
-                    self.error(&peek, "Can't have more than 255 parameters.");
+                    self.error(&peek, "L0106", "Can't have more than 255 parameters.");
                 }
                 params.push(self.consume(TokenType::Identifier, "Expect parameter name.")?);
             }
         }
 
         self.consume(TokenType::RightParen, "Expect ')' after parameters.")?;
+        Ok(params)
+    }
 
-        self.consume(TokenType::LeftBrace, &format!("Expect '{{' before {kind} body."))?;
-        let body = Rc::new(self.block()?);
-        Ok(Rc::new(Stmt::Function(Rc::new(FunctionStmt { name, params: Rc::new(params), body }))))
+    fn lambda(&mut self) -> Result<Expr, LoxResult> {
+        // fun (params) { body }
+        let keyword = self.previous().duplicate();
+        self.consume(TokenType::LeftParen, "Expect '(' after 'fun'.")?;
+        let params = self.parameters()?;
+        self.consume(TokenType::LeftBrace, "Expect '{' before function body.")?;
+        let body = self.block()?;
+        Ok(Expr::Lambda(Rc::new(LambdaExpr {
+            span: self.span_from(keyword.span),
+            keyword,
+            params: Rc::new(params),
+            body: Rc::new(body),
+        })))
+    }
+
+    fn arrow(&mut self) -> Result<Expr, LoxResult> {
+        // (params) => expression, or (params) => { body }
+        let start = self.consume(TokenType::LeftParen, "Expect '(' before parameters.")?.span;
+        let params = self.parameters()?;
+        let keyword = self.consume(TokenType::Arrow, "Expect '=>' after parameters.")?;
+        let body = if self.is_match(&[TokenType::LeftBrace]) {
+            self.block()?
+        } else {
+            let value = self.expression()?;
+            vec![Rc::new(Stmt::Return(Rc::new(ReturnStmt {
+                keyword: keyword.duplicate(),
+                span: value.span(),
+                value: Some(Rc::new(value)),
+            })))]
+        };
+        Ok(Expr::Lambda(Rc::new(LambdaExpr {
+            span: self.span_from(start),
+            keyword,
+            params: Rc::new(params),
+            body: Rc::new(body),
+        })))
+    }
+
+    fn list(&mut self) -> Result<Expr, LoxResult> {
+        // [a, b, c] with an optional trailing comma
+        let bracket = self.previous().duplicate();
+        let mut elements = Vec::new();
+        while !self.check(TokenType::RightBracket) {
+            elements.push(Rc::new(self.expression()?));
+            if !self.is_match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(TokenType::RightBracket, "Expect ']' after list elements.")?;
+        Ok(Expr::List(Rc::new(ListExpr {
+            span: self.span_from(bracket.span),
+            bracket,
+            elements,
+        })))
+    }
+
+    fn map(&mut self) -> Result<Expr, LoxResult> {
+        // {key: value, ...} with an optional trailing comma
+        let brace = self.previous().duplicate();
+        let mut keys = Vec::new();
+        let mut values = Vec::new();
+        while !self.check(TokenType::RightBrace) {
+            keys.push(Rc::new(self.expression()?));
+            self.consume(TokenType::Colon, "Expect ':' after map key.")?;
+            values.push(Rc::new(self.expression()?));
+            if !self.is_match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(TokenType::RightBrace, "Expect '}' after map entries.")?;
+        Ok(Expr::Map(Rc::new(MapExpr {
+            span: self.span_from(brace.span),
+            brace,
+            keys,
+            values,
+        })))
+    }
+
+    fn interpolation(&mut self) -> Result<Expr, LoxResult> {
+        // "a${x}b${y}c" is scanned as Interpolation(a) x Interpolation(b) y String(c)
+        let token = self.previous().duplicate();
+        let mut parts = Vec::new();
+        loop {
+            let text = self.previous();
+            if let Some(Object::Str(s)) = &text.literal {
+                if !s.is_empty() {
+                    parts.push(Rc::new(Expr::Literal(Rc::new(LiteralExpr {
+                        value: text.literal.clone(),
+                        span: text.span,
+                    }))));
+                }
+            }
+            if text.token_type() == TokenType::String {
+                break;
+            }
+            if self.peek().as_string().starts_with('}') {
+                // "${}": the rest of the string would otherwise parse as the expression
+                let peek = self.peek().duplicate();
+                return Err(self.error(&peek, "L0101", "Expect expression."));
+            }
+            parts.push(Rc::new(self.expression()?));
+            if !self.is_match(&[TokenType::Interpolation, TokenType::String]) {
+                let peek = self.peek().duplicate();
+                return Err(self.error(&peek, "L0104", "Expect '}' after interpolated expression."));
+            }
+        }
+        Ok(Expr::Interpolation(Rc::new(InterpolationExpr {
+            span: self.span_from(token.span),
+            token,
+            parts,
+        })))
+    }
+
+    fn is_arrow(&self) -> bool {
+        // look past '(' a, b ')' for '=>' without consuming anything
+        let token_type = |offset: usize| self.tokens.get(self.current + offset).map(|t| t.token_type());
+        if token_type(0) != Some(TokenType::LeftParen) {
+            return false;
+        }
+        let mut offset = 1;
+        if token_type(offset) != Some(TokenType::RightParen) {
+            loop {
+                if token_type(offset) != Some(TokenType::Identifier) {
+                    return false;
+                }
+                offset += 1;
+                match token_type(offset) {
+                    Some(TokenType::Comma) => offset += 1,
+                    Some(TokenType::RightParen) => break,
+                    _ => return false,
+                }
+            }
+        }
+        token_type(offset + 1) == Some(TokenType::Arrow)
     }
 
     fn block(&mut self) -> Result<Vec<Rc<Stmt>>, LoxResult> {
         let mut statements = Vec::new();
 
+        self.block_depth += 1;
         while !self.check(TokenType::RightBrace) && !self.is_at_end() {
-            statements.push(self.declaration()?);
+            let result = self.declaration();
+            if self.too_deep {
+                // give up on the block rather than report every brace left open
+                self.block_depth -= 1;
+                return result.map(|_| statements);
+            }
+            if let Ok(statement) = result {
+                statements.push(statement);
+            }
         }
+        self.block_depth -= 1;
 
         self.consume(TokenType::RightBrace, "Expect '}' after block.")?;
         Ok(statements)
@@ -292,78 +527,102 @@ impl Parser<'_> {
 
         if self.is_match(&[TokenType::Equal]) {
             let equals = self.previous().duplicate();
-            let value = self.assignment()?;
+            let value = self.nested(|p| p.assignment())?;
 
+            let span = expr.span().to(value.span());
             if let Expr::Variable(expr) = expr {
                 return Ok(Expr::Assign(Rc::new(AssignExpr {
                     name: expr.name.duplicate(),
                     value: Rc::new(value),
+                    span,
                 })));
             } else if let Expr::Get(expr) = expr {
                 return Ok(Expr::Set(Rc::new(SetExpr {
                     object: Rc::clone(&expr.object), 
                     name: expr.name.duplicate(),
                     value: Rc::new(value),
+                    span,
+                })))
+            } else if let Expr::Index(expr) = expr {
+                return Ok(Expr::IndexSet(Rc::new(IndexSetExpr {
+                    object: Rc::clone(&expr.object),
+                    bracket: expr.bracket.duplicate(),
+                    index: Rc::clone(&expr.index),
+                    value: Rc::new(value),
+                    span,
                 })))
             }
 
-            self.error(&equals, "Invalid assignment target.");
+            self.error(&equals, "L0105", "Invalid assignment target.");
         }
 
         Ok(expr)
     }
 
     fn or(&mut self) -> Result<Expr, LoxResult> {
+        let depth = self.depth;
         let mut expr = self.and()?;
 
         while self.is_match(&[TokenType::Or]) {
             let operator = self.previous().duplicate();
+            self.deeper()?;
             let right = self.and()?;
             expr = Expr::Logical(Rc::new(LogicalExpr {
+                span: expr.span().to(right.span()),
                 left: Rc::new(expr),
                 operator,
                 right: Rc::new(right),
             }));
         }
 
+        self.depth = depth;
         Ok(expr)
     }
 
     fn and(&mut self) -> Result<Expr, LoxResult> {
+        let depth = self.depth;
         let mut expr = self.equality()?;
 
         while self.is_match(&[TokenType::And]) {
             let operator = self.previous().duplicate();
+            self.deeper()?;
             let right = self.equality()?;
             expr = Expr::Logical(Rc::new(LogicalExpr {
+                span: expr.span().to(right.span()),
                 left: Rc::new(expr),
                 operator,
                 right: Rc::new(right),
             }));
         }
 
+        self.depth = depth;
         Ok(expr)
     }
 
     fn equality(&mut self) -> Result<Expr, LoxResult> {
         // equality => comparison ( ( != | == ) comparison )*
+        let depth = self.depth;
         let mut expr = self.comparison()?;
 
         while self.is_match(&[TokenType::BangEqual, TokenType::EqualEqual]) {
             let operator = self.previous().duplicate();
+            self.deeper()?;
             let right = self.comparison()?;
             expr = Expr::Binary(Rc::new(BinaryExpr {
+                span: expr.span().to(right.span()),
                 left: Rc::new(expr),
                 operator,
                 right: Rc::new(right),
             }));
         }
 
+        self.depth = depth;
         Ok(expr)
     }
 
     fn comparison(&mut self) -> Result<Expr, LoxResult> {
         // comparison => term ( ( > | >= | < | <= ) term )*
+        let depth = self.depth;
         let mut expr = self.term()?;
 
         while self.is_match(&[
@@ -373,48 +632,59 @@ impl Parser<'_> {
             TokenType::LessEqual,
         ]) {
             let operator = self.previous().duplicate();
+            self.deeper()?;
             let right = self.term()?;
             expr = Expr::Binary(Rc::new(BinaryExpr {
+                span: expr.span().to(right.span()),
                 left: Rc::new(expr),
                 operator,
                 right: Rc::new(right),
             }));
         }
 
+        self.depth = depth;
         Ok(expr)
     }
 
     fn term(&mut self) -> Result<Expr, LoxResult> {
         // term => factor ( ( - | + ) factor )*
+        let depth = self.depth;
         let mut expr = self.factor()?;
 
         while self.is_match(&[TokenType::Minus, TokenType::Plus]) {
             let operator = self.previous().duplicate();
+            self.deeper()?;
             let right = self.factor()?;
             expr = Expr::Binary(Rc::new(BinaryExpr {
+                span: expr.span().to(right.span()),
                 left: Rc::new(expr),
                 operator,
                 right: Rc::new(right),
             }));
         }
 
+        self.depth = depth;
         Ok(expr)
     }
 
     fn factor(&mut self) -> Result<Expr, LoxResult> {
         // factor => unary ( ( * | \ ) unary )*
+        let depth = self.depth;
         let mut expr = self.unary()?;
 
         while self.is_match(&[TokenType::Slash, TokenType::Star]) {
             let operator = self.previous().duplicate();
+            self.deeper()?;
             let right = self.unary()?;
             expr = Expr::Binary(Rc::new(BinaryExpr {
+                span: expr.span().to(right.span()),
                 left: Rc::new(expr),
                 operator,
                 right: Rc::new(right),
             }));
         }
 
+        self.depth = depth;
         Ok(expr)
     }
 
@@ -423,8 +693,9 @@ impl Parser<'_> {
         //       |  primary
         if self.is_match(&[TokenType::Bang, TokenType::Minus]) {
             let operator = self.previous().duplicate();
-            let right = self.unary()?;
+            let right = self.nested(|p| p.unary())?;
             return Ok(Expr::Unary(Rc::new(UnaryExpr {
+                span: operator.span.to(right.span()),
                 operator,
                 right: Rc::new(right),
             })));
@@ -434,30 +705,70 @@ impl Parser<'_> {
     }
 
     fn call(&mut self) -> Result<Expr, LoxResult> {
+        let depth = self.depth;
         let mut expr = self.primary()?;
 
         loop {
+            if self.check(TokenType::LeftParen) || self.check(TokenType::Dot) || self.check(TokenType::LeftBracket) {
+                self.deeper()?;
+            }
             if self.is_match(&[TokenType::LeftParen]) {
                 expr = self.finish_call(&Rc::new(expr))?;
             } else if self.is_match(&[TokenType::Dot]) {
                 let name = self.consume(TokenType::Identifier, "Expect property name after '.'.")?;
-                expr = Expr::Get(Rc::new(GetExpr { object: Rc::new(expr), name }));
+                expr = Expr::Get(Rc::new(GetExpr { span: expr.span().to(name.span), object: Rc::new(expr), name }));
+            } else if self.is_match(&[TokenType::LeftBracket]) {
+                let bracket = self.previous().duplicate();
+                if self.is_match(&[TokenType::Colon]) {
+                    expr = self.slice(expr, bracket, None)?;
+                    continue;
+                }
+                let index = self.expression()?;
+                if self.is_match(&[TokenType::Colon]) {
+                    expr = self.slice(expr, bracket, Some(Rc::new(index)))?;
+                    continue;
+                }
+                let close = self.consume(TokenType::RightBracket, "Expect ']' after index.")?;
+                expr = Expr::Index(Rc::new(IndexExpr {
+                    span: expr.span().to(close.span),
+                    object: Rc::new(expr),
+                    bracket,
+                    index: Rc::new(index),
+                }));
             } else {
                 break;
             }
         }
 
+        self.depth = depth;
         Ok(expr)
     }
 
+    fn slice(&mut self, object: Expr, bracket: Token, start: Option<Rc<Expr>>) -> Result<Expr, LoxResult> {
+        // object[start:end]; either bound may be left out, and the ':' is already consumed
+        let end = if self.check(TokenType::RightBracket) {
+            None
+        } else {
+            Some(Rc::new(self.expression()?))
+        };
+        let close = self.consume(TokenType::RightBracket, "Expect ']' after slice.")?;
+        Ok(Expr::Slice(Rc::new(SliceExpr {
+            span: object.span().to(close.span),
+            object: Rc::new(object),
+            bracket,
+            start,
+            end,
+        })))
+    }
+
     fn finish_call(&mut self, callee: &Rc<Expr>) -> Result<Expr, LoxResult> {
         let mut arguments = Vec::new();
         if !self.check(TokenType::RightParen) {
             arguments.push(Rc::new(self.expression()?));
             while self.is_match(&[TokenType::Comma]) {
-                if arguments.len() >= 255 && !self.had_error {
+                if arguments.len() == 255 {
                     let peek = self.peek().duplicate();
-                    self.error(&peek, "Can't have more than 255 arguments.");
+                    self.error(&peek, "L0106", "Can't have more than 255 arguments.");
                 }
                 arguments.push(Rc::new(self.expression()?));
             }
@@ -469,53 +780,77 @@ impl Parser<'_> {
         )?;
         Ok(Expr::Call(Rc::new(CallExpr {
             callee: Rc::clone(callee),
+            span: callee.span().to(paren.span),
             paren,
             arguments,
         })))
     }
 
     fn primary(&mut self) -> Result<Expr, LoxResult> {
-        // primary => NUMBER | STRING | true | false | nil | ( expression )
+        // primary => NUMBER | STRING | interpolation | true | false | nil | ( expression ) | [ list ] | { map } | lambda
         if self.is_match(&[TokenType::False]) {
             return Ok(Expr::Literal(Rc::new(LiteralExpr {
                 value: Some(Object::Bool(false)),
+                span: self.previous().span,
             })));
         }
         if self.is_match(&[TokenType::True]) {
             return Ok(Expr::Literal(Rc::new(LiteralExpr {
                 value: Some(Object::Bool(true)),
+                span: self.previous().span,
             })));
         }
         if self.is_match(&[TokenType::Nil]) {
             return Ok(Expr::Literal(Rc::new(LiteralExpr {
                 value: Some(Object::Nil),
+                span: self.previous().span,
             })));
         }
 
         if self.is_match(&[TokenType::Number, TokenType::String]) {
             return Ok(Expr::Literal(Rc::new(LiteralExpr {
                 value: self.previous().literal.clone(),
+                span: self.previous().span,
             })));
         }
+        if self.is_match(&[TokenType::Interpolation]) {
+            return self.interpolation();
+        }
         if self.is_match(&[TokenType::Super]) {
             let keyword = self.previous().duplicate();
             self.consume(TokenType::Dot, "Expect '.' after 'super'.")?;
             let method = self.consume(TokenType::Identifier, "Expect superclass method name.")?;
             return Ok(Expr::Super(Rc::new(SuperExpr {
+                span: keyword.span.to(method.span),
                 keyword, method
             })));
         }
         if self.is_match(&[TokenType::This]) {
             return Ok(Expr::This(Rc::new(ThisExpr {
                 keyword: self.previous().duplicate(),
+                span: self.previous().span,
             })));
         }
         if self.is_match(&[TokenType::Identifier]) {
             return Ok(Expr::Variable(Rc::new(VariableExpr {
                 name: self.previous().duplicate(),
+                span: self.previous().span,
             })));
         }
+        if self.is_match(&[TokenType::Fun]) {
+            return self.lambda();
+        }
+        if self.is_match(&[TokenType::LeftBracket]) {
+            return self.list();
+        }
+        if self.is_match(&[TokenType::LeftBrace]) {
+            return self.map();
+        }
+        if self.is_arrow() {
+            return self.arrow();
+        }
         if self.is_match(&[TokenType::LeftParen]) {
+            let start = self.previous().span;
             let expr = self.expression()?;
             self.consume(
                 TokenType::RightParen,
@@ -523,11 +858,12 @@ impl Parser<'_> {
             )?;
             return Ok(Expr::Grouping(Rc::new(GroupingExpr {
                 expression: Rc::new(expr),
+                span: self.span_from(start),
             })));
         }
 
         let peek = self.peek().duplicate();
-        Err(LoxResult::parse_error(&peek, "Expect expression."))
+        Err(self.error(&peek, "L0101", "Expect expression."))
     }
 
     fn consume(&mut self, ttype: TokenType, message: &str) -> Result<Token, LoxResult> {
@@ -535,7 +871,22 @@ impl Parser<'_> {
             Ok(self.advance().duplicate())
         } else {
             let peek = self.peek().duplicate();
-            Err(self.error(&peek, message))
+            Err(self.error(&peek, Parser::missing(ttype), message))
+        }
+    }
+
+    fn missing(ttype: TokenType) -> &'static str {
+        // the code for a missing token depends only on what was expected
+        match ttype {
+            TokenType::Semicolon => "L0102",
+            TokenType::LeftParen | TokenType::RightParen => "L0103",
+            TokenType::LeftBrace | TokenType::RightBrace => "L0104",
+            TokenType::Dot => "L0108",
+            TokenType::RightBracket => "L0109",
+            TokenType::Colon => "L0110",
+            TokenType::Arrow => "L0112",
+            // otherwise a name: of a variable, function, class, parameter or property
+            _ => "L0107",
         }
     }
 
@@ -557,6 +908,10 @@ impl Parser<'_> {
         }
     }
 
+    fn check_next(&self, ttype: TokenType) -> bool {
+        self.tokens.get(self.current + 1).is_some_and(|t| t.is(ttype))
+    }
+
     fn advance(&mut self) -> &Token {
         if !self.is_at_end() {
             self.current += 1;
@@ -576,33 +931,54 @@ impl Parser<'_> {
         self.tokens.get(self.current - 1).unwrap()
     }
 
-    fn error(&mut self, token: &Token, message: &str) -> LoxResult {
+    fn span_from(&self, start: Span) -> Span {
+        // span from start through the most recently consumed token
+        start.to(self.previous().span)
+    }
+
+    fn error(&mut self, token: &Token, code: &'static str, message: &str) -> LoxResult {
         self.had_error = true;
-        LoxResult::parse_error(token, message)
+        let e = LoxResult::parse_error(token, code, message);
+        self.diagnostics.report(&e);
+        e
     }
 
     fn synchronize(&mut self) {
-        self.advance();
-
+        // skip to the start of the next statement, treating a nested block
+        // as part of the broken statement
+        let mut depth = 0;
         while !self.is_at_end() {
-            if self.previous().is(TokenType::Semicolon) {
-                return;
-            }
-
-            if matches!(
-                self.peek().token_type(),
+            match self.peek().token_type() {
+                TokenType::Semicolon if depth == 0 => {
+                    self.advance();
+                    return;
+                }
+                TokenType::LeftBrace => depth += 1,
+                // leave the brace for the enclosing block to close
+                TokenType::RightBrace if depth == 0 && self.block_depth > 0 => return,
+                TokenType::RightBrace if depth > 0 => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.advance();
+                        return;
+                    }
+                }
                 TokenType::Class
-                    | TokenType::Fun
-                    | TokenType::Var
-                    | TokenType::For
-                    | TokenType::If
-                    | TokenType::While
-                    | TokenType::Print
-                    | TokenType::Return
-            ) {
-                return;
+                | TokenType::Fun
+                | TokenType::Var
+                | TokenType::For
+                | TokenType::If
+                | TokenType::While
+                | TokenType::Print
+                | TokenType::Return
+                | TokenType::Break
+                | TokenType::Continue
+                    if depth == 0 =>
+                {
+                    return;
+                }
+                _ => {}
             }
-
             self.advance();
         }
     }
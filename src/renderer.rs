@@ -0,0 +1,185 @@
+use crate::diagnostic::*;
+use crate::token::*;
+use std::env;
+use std::io::{self, IsTerminal};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+
+const TAB_WIDTH: usize = 4;
+const TRACE_LIMIT: usize = 12;
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum ColorMode {
+    Plain,
+    Ansi,
+}
+
+impl ColorMode {
+    pub fn detect() -> ColorMode {
+        // color only when writing to a terminal and NO_COLOR is unset
+        if io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none() {
+            ColorMode::Ansi
+        } else {
+            ColorMode::Plain
+        }
+    }
+}
+
+struct Annotation<'a> {
+    span: Span,
+    marker: char,
+    style: &'a str,
+    message: &'a str,
+}
+
+pub struct Renderer<'a> {
+    path: &'a str,
+    source: &'a str,
+    color: ColorMode,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(path: &'a str, source: &'a str, color: ColorMode) -> Renderer<'a> {
+        Renderer { path, source, color }
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let (title, title_style) = match diagnostic.severity {
+            Severity::Error => ("error", RED),
+            Severity::Warning => ("warning", YELLOW),
+        };
+        let title = match diagnostic.code {
+            Some(code) => format!("{title}[{code}]:"),
+            None => format!("{title}:"),
+        };
+        let mut out = format!(
+            "{}{}\n",
+            self.paint(&title, title_style),
+            self.paint(&format!(" {}", diagnostic.message), BOLD)
+        );
+
+        if diagnostic.kind == DiagnosticKind::System {
+            return out;
+        }
+
+        // the primary span is only trusted if it still points at the reported token
+        let primary = diagnostic.span.filter(|span| match &diagnostic.token {
+            Some(token) => self.slice(*span) == Some(token.as_string().as_str()),
+            None => self.slice(*span).is_some(),
+        });
+
+        let mut annotations = Vec::new();
+        if let Some(span) = primary {
+            annotations.push(Annotation { span, marker: '^', style: title_style, message: "" });
+        }
+        for label in &diagnostic.labels {
+            if self.slice(label.span).is_some() {
+                annotations.push(Annotation { span: label.span, marker: '-', style: BLUE, message: &label.message });
+            }
+        }
+        annotations.sort_by_key(|a| (a.span.line, a.span.column));
+
+        let width = annotations
+            .iter()
+            .map(|a| a.span.line.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let pad = " ".repeat(width);
+        let gutter = self.paint("|", BLUE);
+
+        match primary {
+            Some(span) => out.push_str(&format!(
+                "{pad}{} {}:{}:{}\n",
+                self.paint("-->", BLUE),
+                self.path,
+                span.line,
+                span.column
+            )),
+            None => out.push_str(&format!("{pad}{} {}:{}\n", self.paint("-->", BLUE), self.path, diagnostic.line)),
+        }
+
+        if !annotations.is_empty() {
+            out.push_str(&format!("{pad} {gutter}\n"));
+        }
+
+        let mut previous_line = None;
+        for annotation in &annotations {
+            let line = annotation.span.line;
+            let text = self.source.lines().nth(line - 1).unwrap_or("");
+            if previous_line != Some(line) {
+                out.push_str(&format!(
+                    "{} {gutter} {}\n",
+                    self.paint(&format!("{line:>width$}"), BLUE),
+                    text.replace('\t', &" ".repeat(TAB_WIDTH))
+                ));
+                previous_line = Some(line);
+            }
+
+            let prefix: String = text.chars().take(annotation.span.column - 1).collect();
+            let length = if annotation.span.end_line == line {
+                annotation.span.end_column.saturating_sub(annotation.span.column)
+            } else {
+                text.chars().count().saturating_sub(annotation.span.column - 1)
+            };
+            let underline = annotation.marker.to_string().repeat(length.max(1));
+            let marked = if annotation.message.is_empty() {
+                underline
+            } else {
+                format!("{underline} {}", annotation.message)
+            };
+            out.push_str(&format!(
+                "{pad} {gutter} {}{}\n",
+                " ".repeat(Self::display_width(&prefix)),
+                self.paint(&marked, annotation.style)
+            ));
+        }
+
+        for note in &diagnostic.notes {
+            out.push_str(&format!("{pad} {} {} {note}\n", self.paint("=", BLUE), self.paint("help:", BOLD)));
+        }
+
+        // a lone <script> frame adds nothing to the location above
+        if diagnostic.trace.len() > 1 {
+            out.push_str(&format!("{pad} {} {}\n", self.paint("=", BLUE), self.paint("backtrace:", BOLD)));
+            let trace = &diagnostic.trace;
+            if trace.len() > TRACE_LIMIT {
+                // deep recursion repeats itself; keep the innermost and outermost frames
+                for frame in &trace[..TRACE_LIMIT - 2] {
+                    out.push_str(&format!("{pad}     {frame}\n"));
+                }
+                out.push_str(&format!("{pad}     ... {} frames omitted\n", trace.len() - TRACE_LIMIT));
+                for frame in &trace[trace.len() - 2..] {
+                    out.push_str(&format!("{pad}     {frame}\n"));
+                }
+            } else {
+                for frame in trace {
+                    out.push_str(&format!("{pad}     {frame}\n"));
+                }
+            }
+        }
+
+        out
+    }
+
+    fn slice(&self, span: Span) -> Option<&str> {
+        if span.line == 0 || span.column == 0 {
+            return None;
+        }
+        self.source.get(span.start..span.end)
+    }
+
+    fn display_width(text: &str) -> usize {
+        text.chars().map(|c| if c == '\t' { TAB_WIDTH } else { 1 }).sum()
+    }
+
+    fn paint(&self, text: &str, style: &str) -> String {
+        match self.color {
+            ColorMode::Plain => text.to_string(),
+            ColorMode::Ansi => format!("{style}{text}{RESET}"),
+        }
+    }
+}
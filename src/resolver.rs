@@ -1,20 +1,70 @@
+use crate::diagnostic::*;
+use crate::callable::*;
 use crate::interpreter::*;
 use crate::stmt::*;
 use crate::expr::*;
 use crate::error::*;
+use crate::stack::*;
 use crate::token::*;
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
 
 pub struct Resolver<'a> {
     interpreter: &'a Interpreter,
-    scopes: RefCell<Vec<RefCell<HashMap<String, bool>>>>,
+    scopes: RefCell<Vec<RefCell<HashMap<String, Local>>>>,
     current_function: RefCell<FunctionType>,   
     current_class: RefCell<ClassType>,
     in_loop: RefCell<bool>,
     had_error: RefCell<bool>,
+    globals: RefCell<HashMap<String, Span>>,
+    function_scope: RefCell<usize>,
+    loop_reads: RefCell<Vec<HashSet<(usize, String)>>>,
+    // straight-line stretch of code being resolved; a new one starts wherever
+    // control may branch, join or jump
+    flow: RefCell<usize>,
+    global_calls: RefCell<Vec<GlobalCall>>,
+    assigned_globals: RefCell<HashSet<String>>,
+    stack_base: RefCell<Option<usize>>,
+    lints: RefCell<bool>,
+    // local functions whose bodies are being resolved, by scope and name
+    defining: RefCell<Vec<(usize, String)>>,
+}
+
+struct GlobalCall {
+    callee: Token,
+    paren: Token,
+    arguments: usize,
+}
+
+// a top-level callable whose arity is known before running
+struct Signature {
+    arity: usize,
+    declaration: Option<Span>,
+    is_class: bool,
+}
+
+struct Local {
+    defined: bool,
+    used: bool,
+    kind: LocalKind,
+    declaration: Span,
+    // last assignment in this function that nothing has read yet, and the
+    // stretch of straight-line code it was made in
+    pending: Option<(Span, usize)>,
+    // assignments replaced by a later one in the same stretch before any read
+    overwritten: Vec<Span>,
+    captured: bool,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+enum LocalKind {
+    Variable,
+    Parameter,
+    Function,
+    Class,
+    Internal,
 }
 
 #[derive(PartialEq)]
@@ -36,24 +86,24 @@ impl StmtVisitor<()> for Resolver<'_> {
     fn visit_class_stmt(&self, _: Rc<Stmt>, stmt: &ClassStmt) -> Result<(), LoxResult> {
         let enclosing_class = self.current_class.replace(ClassType::Class);
 
-        self.declare(&stmt.name);
+        self.declare(&stmt.name, LocalKind::Class);
         self.define(&stmt.name);
            
         if let Some(superclass) = &stmt.superclass {
             self.current_class.replace(ClassType::Subclass);
             if let Expr::Variable(v) = &superclass.deref() {
                 if v.name.as_string() == stmt.name.as_string() {
-                    self.error(&v.name, "A class can't inherit from itself.");
+                    self.error(&v.name, "L0207", "A class can't inherit from itself.");
                 }
             self.resolve_expr(superclass.clone())?;
             }
 
             self.begin_scope();
-            self.scopes.borrow().last().unwrap().borrow_mut().insert("super".to_string(), true);
+            self.define_internal("super");
         }
 
         self.begin_scope();
-        self.scopes.borrow().last().unwrap().borrow_mut().insert("this".to_string(), true);
+        self.define_internal("this");
         
         for method in stmt.methods.deref() {
             if let Stmt::Function(method) = method.deref() {
@@ -62,12 +112,9 @@ impl StmtVisitor<()> for Resolver<'_> {
                 } else {
                     FunctionType::Method
                 };
-                self.resolve_function(method, declaration)?;
+                self.resolve_function(&method.params, method.body.clone(), declaration)?;
             } else {
-                return Err(LoxResult::runtime_error(
-                    &stmt.name,
-                    "Class method is not a function."
-                ))
+                self.error(&stmt.name, "L0212", "Class method is not a function.");
             }
         }
 
@@ -83,11 +130,11 @@ impl StmtVisitor<()> for Resolver<'_> {
     
     fn visit_return_stmt(&self, _: Rc<Stmt>, stmt: &ReturnStmt) -> Result<(), LoxResult> {
         if *self.current_function.borrow() == FunctionType::None {
-            self.error(&stmt.keyword, "Can't return from top-level code.");
+            self.error(&stmt.keyword, "L0201", "Can't return from top-level code.");
         }
         if let Some(value) = stmt.value.clone() {
             if *self.current_function.borrow() == FunctionType::Initializer {
-                self.error(&stmt.keyword, "Can't return a value from an initializer.")
+                self.error(&stmt.keyword, "L0202", "Can't return a value from an initializer.")
             } else {
                 self.resolve_expr(value)?;
             }
@@ -96,24 +143,40 @@ impl StmtVisitor<()> for Resolver<'_> {
     }
     
     fn visit_function_stmt(&self, _: Rc<Stmt>, stmt: &FunctionStmt) -> Result<(), LoxResult> {
-        self.declare(&stmt.name);
+        self.declare(&stmt.name, LocalKind::Function);
         self.define(&stmt.name);
-        self.resolve_function(stmt, FunctionType::Function)?;
-
-        Ok(())
+        // a local function that only calls itself is still unused
+        let scope = self.scopes.borrow().len().checked_sub(1);
+        if let Some(scope) = scope {
+            self.defining.borrow_mut().push((scope, stmt.name.as_string().to_string()));
+        }
+        let result = self.resolve_function(&stmt.params, stmt.body.clone(), FunctionType::Function);
+        if scope.is_some() {
+            self.defining.borrow_mut().pop();
+        }
+        result
 
     }
     
     fn visit_break_stmt(&self, _: Rc<Stmt>, stmt: &BreakStmt) -> Result<(), LoxResult> {
         if !*self.in_loop.borrow() {
-            self.error(&stmt.token, "Can't break from top-level code.");
+            self.error(&stmt.token, "L0203", "Can't break from top-level code.");
         }
+        self.branch();
+        Ok(())
+    }
+
+    fn visit_continue_stmt(&self, _: Rc<Stmt>, stmt: &ContinueStmt) -> Result<(), LoxResult> {
+        if !*self.in_loop.borrow() {
+            self.error(&stmt.token, "L0211", "Can't use 'continue' outside of a loop.");
+        }
+        self.branch();
         Ok(())
     }
     
     fn visit_block_stmt(&self, _: Rc<Stmt>, stmt: &BlockStmt) -> Result<(), LoxResult> {
         self.begin_scope();
-        self.resolve(stmt.statements.clone())?;
+        self.resolve_stmts(stmt.statements.clone())?;
         self.end_scope();
         Ok(())
     }
@@ -125,10 +188,13 @@ impl StmtVisitor<()> for Resolver<'_> {
     
     fn visit_if_stmt(&self, _: Rc<Stmt>, stmt: &IfStmt) -> Result<(), LoxResult> {
         self.resolve_expr(stmt.condition.clone())?;
+        self.branch();
         self.resolve_stmt(stmt.then_branch.clone())?;
         if let Some(else_branch) = stmt.else_branch.clone() {
+            self.branch();
             self.resolve_stmt(else_branch)?;
         }
+        self.branch();
         Ok(())
     }
 
@@ -138,7 +204,7 @@ impl StmtVisitor<()> for Resolver<'_> {
     }
     
     fn visit_var_stmt(&self, _: Rc<Stmt>, stmt: &VarStmt) -> Result<(), LoxResult> {
-        self.declare(&stmt.name);
+        self.declare(&stmt.name, LocalKind::Variable);
         if let Some(initializer) = &stmt.initializer {
             self.resolve_expr(initializer.clone())?;
         }
@@ -148,15 +214,40 @@ impl StmtVisitor<()> for Resolver<'_> {
     
     fn visit_while_stmt(&self, _: Rc<Stmt>, stmt: &WhileStmt) -> Result<(), LoxResult> {
         let previous_nesting = self.in_loop.replace(true);
+        self.loop_reads.borrow_mut().push(HashSet::new());
+        self.branch();
         self.resolve_expr(stmt.condition.clone())?;
         self.resolve_stmt(stmt.body.clone())?;
+        if let Some(increment) = stmt.increment.clone() {
+            self.resolve_expr(increment)?;
+        }
         self.in_loop.replace(previous_nesting);
+        self.branch();
+
+        // the next iteration may read what the end of the body assigned
+        let reads = self.loop_reads.borrow_mut().pop().unwrap_or_default();
+        for (index, name) in reads {
+            if let Some(scope) = self.scopes.borrow().get(index) {
+                if let Some(local) = scope.borrow_mut().get_mut(&name) {
+                    local.pending = None;
+                }
+            }
+        }
         Ok(())
     }
 }
 
 impl ExprVisitor<()> for Resolver<'_> {
     fn visit_call_expr(&self, _: Rc<Expr>, expr: &CallExpr) -> Result<(), LoxResult> { 
+        if let Expr::Variable(callee) = expr.callee.deref() {
+            if !self.is_local(&callee.name) {
+                self.global_calls.borrow_mut().push(GlobalCall {
+                    callee: callee.name.duplicate(),
+                    paren: expr.paren.duplicate(),
+                    arguments: expr.arguments.len(),
+                });
+            }
+        }
         self.resolve_expr(expr.callee.clone())?;
         for argument in expr.arguments.iter() {
             self.resolve_expr(argument.clone())?;
@@ -171,7 +262,10 @@ impl ExprVisitor<()> for Resolver<'_> {
 
     fn visit_assign_expr(&self, wrapper: Rc<Expr>, expr: &AssignExpr) -> Result<(), LoxResult> { 
         self.resolve_expr(expr.value.clone())?;
-        self.resolve_local(wrapper, &expr.name)?;
+        if !self.is_local(&expr.name) {
+            self.assigned_globals.borrow_mut().insert(expr.name.as_string().to_string());
+        }
+        self.resolve_local(wrapper, &expr.name, false)?;
         Ok(())
     }
 
@@ -181,7 +275,9 @@ impl ExprVisitor<()> for Resolver<'_> {
 
     fn visit_logical_expr(&self, _: Rc<Expr>, expr: &LogicalExpr) -> Result<(), LoxResult> { 
         self.resolve_expr(expr.left.clone())?;
+        self.branch();
         self.resolve_expr(expr.right.clone())?;
+        self.branch();
         Ok(()) 
     }
 
@@ -193,23 +289,73 @@ impl ExprVisitor<()> for Resolver<'_> {
 
     fn visit_super_expr(&self, wrapper: Rc<Expr>, expr: &SuperExpr) -> Result<(), LoxResult> {
         match self.current_class.borrow().deref() {
-            ClassType::None => self.error(&expr.keyword, "Can't use 'super' outside of a class."),
+            ClassType::None => self.error(&expr.keyword, "L0205", "Can't use 'super' outside of a class."),
             ClassType::Subclass => {},
-            _ => self.error(&expr.keyword, "Can't use 'super' in a class with no superclass."),
+            _ => self.error(&expr.keyword, "L0206", "Can't use 'super' in a class with no superclass."),
         }
-        let _ = self.resolve_local(wrapper, &expr.keyword);
+        let _ = self.resolve_local(wrapper, &expr.keyword, true);
         Ok(())
     }
 
     fn visit_this_expr(&self, wrapper: Rc<Expr>, expr: &ThisExpr) -> Result<(), LoxResult> {
         if *self.current_class.borrow() == ClassType::None {
-            self.error(&expr.keyword, "Can't use 'this' outside of a class.");
+            self.error(&expr.keyword, "L0204", "Can't use 'this' outside of a class.");
             return Ok(());
         }
-        let _ = self.resolve_local(wrapper, &expr.keyword);
+        let _ = self.resolve_local(wrapper, &expr.keyword, true);
+        Ok(())
+    }
+
+    fn visit_list_expr(&self, _: Rc<Expr>, expr: &ListExpr) -> Result<(), LoxResult> {
+        for element in expr.elements.iter() {
+            self.resolve_expr(element.clone())?;
+        }
+        Ok(())
+    }
+
+    fn visit_slice_expr(&self, _: Rc<Expr>, expr: &SliceExpr) -> Result<(), LoxResult> {
+        self.resolve_expr(expr.object.clone())?;
+        if let Some(start) = &expr.start {
+            self.resolve_expr(start.clone())?;
+        }
+        if let Some(end) = &expr.end {
+            self.resolve_expr(end.clone())?;
+        }
+        Ok(())
+    }
+
+    fn visit_interpolation_expr(&self, _: Rc<Expr>, expr: &InterpolationExpr) -> Result<(), LoxResult> {
+        for part in expr.parts.iter() {
+            self.resolve_expr(part.clone())?;
+        }
+        Ok(())
+    }
+
+    fn visit_map_expr(&self, _: Rc<Expr>, expr: &MapExpr) -> Result<(), LoxResult> {
+        for (key, value) in expr.keys.iter().zip(expr.values.iter()) {
+            self.resolve_expr(key.clone())?;
+            self.resolve_expr(value.clone())?;
+        }
+        Ok(())
+    }
+
+    fn visit_index_expr(&self, _: Rc<Expr>, expr: &IndexExpr) -> Result<(), LoxResult> {
+        self.resolve_expr(expr.object.clone())?;
+        self.resolve_expr(expr.index.clone())?;
+        Ok(())
+    }
+
+    fn visit_index_set_expr(&self, _: Rc<Expr>, expr: &IndexSetExpr) -> Result<(), LoxResult> {
+        self.resolve_expr(expr.object.clone())?;
+        self.resolve_expr(expr.index.clone())?;
+        self.resolve_expr(expr.value.clone())?;
         Ok(())
     }
 
+    fn visit_lambda_expr(&self, _: Rc<Expr>, expr: &LambdaExpr) -> Result<(), LoxResult> {
+        self.resolve_function(&expr.params, expr.body.clone(), FunctionType::Function)
+    }
+
     fn visit_grouping_expr(&self, _: Rc<Expr>, expr: &GroupingExpr) -> Result<(), LoxResult> { 
         self.resolve_expr(expr.expression.clone())?;
         Ok(()) 
@@ -234,13 +380,14 @@ impl ExprVisitor<()> for Resolver<'_> {
                 .unwrap()
                 .borrow()
                 .get(expr.name.as_string())
-                == Some(&false) 
+                .map(|local| local.defined)
+                == Some(false) 
         {
-            Err(LoxResult::resolver_error(&expr.name, "Can't read local variable in its own initializer."))
+            self.error(&expr.name, "L0209", "Can't read local variable in its own initializer.");
         } else { 
-            self.resolve_local(wrapper, &expr.name)?;
-            Ok(())
+            self.resolve_local(wrapper, &expr.name, true)?;
         }
+        Ok(())
     }
 }
 
@@ -253,14 +400,145 @@ impl<'a> Resolver<'a> {
             current_class: RefCell::new(ClassType::None),
             in_loop: RefCell::new(false),
             had_error: RefCell::new(false),
+            globals: RefCell::new(HashMap::new()),
+            function_scope: RefCell::new(0),
+            loop_reads: RefCell::new(Vec::new()),
+            flow: RefCell::new(0),
+            global_calls: RefCell::new(Vec::new()),
+            assigned_globals: RefCell::new(HashSet::new()),
+            stack_base: RefCell::new(None),
+            lints: RefCell::new(true),
+            defining: RefCell::new(Vec::new()),
         }
     }
 
+    pub fn set_lints(&self, enabled: bool) {
+        // unused and unreachable code can't be judged when statements failed to parse
+        self.lints.replace(enabled);
+    }
+
     pub fn resolve(&self, statements: Rc<Vec<Rc<Stmt>>>) -> Result<(), LoxResult> {
+        self.stack_base.replace(Some(stack_address()));
+        let result = self.resolve_stmts(statements.clone());
+        self.check_arities(&statements);
+        result
+    }
+
+    fn resolve_stmts(&self, statements: Rc<Vec<Rc<Stmt>>>) -> Result<(), LoxResult> {
+        // errors are reported as they are found, so one bad statement
+        // doesn't hide problems in the rest
+        let mut result = Ok(());
+        let mut exit: Option<Span> = None;
+        let mut reported = false;
         for statement in statements.iter() {
-            self.resolve_stmt(statement.clone())?;
+            if let Some(exit) = exit.filter(|_| !reported && *self.lints.borrow()) {
+                self.report(
+                    Diagnostic::new(DiagnosticKind::Resolution, Severity::Warning, Some("L0404"), "Unreachable code.", statement.span().line, None)
+                        .with_span(statement.span())
+                        .with_label(exit, "any code following this statement is unreachable"),
+                );
+                reported = true;
+            }
+            if let Err(e) = self.resolve_stmt(statement.clone()) {
+                result = result.and(Err(e));
+            }
+            if exit.is_none() && matches!(statement.deref(), Stmt::Return(_) | Stmt::Break(_) | Stmt::Continue(_)) {
+                exit = Some(statement.span());
+            }
+        }
+        result
+    }
+
+    fn check_arities(&self, statements: &[Rc<Stmt>]) {
+        // only names bound exactly once and never reassigned are trusted
+        let mut declarations: HashMap<String, usize> = HashMap::new();
+        for statement in statements {
+            let name = match statement.deref() {
+                Stmt::Function(f) => &f.name,
+                Stmt::Class(c) => &c.name,
+                Stmt::Var(v) => &v.name,
+                _ => continue,
+            };
+            *declarations.entry(name.as_string().to_string()).or_default() += 1;
+        }
+
+        let mut signatures: HashMap<String, Signature> = HashMap::new();
+        for statement in statements {
+            let (name, signature) = match statement.deref() {
+                Stmt::Function(f) => (
+                    &f.name,
+                    Some(Signature { arity: f.params.len(), declaration: Some(f.name.span), is_class: false }),
+                ),
+                Stmt::Class(c) => (&c.name, self.class_signature(c, &signatures)),
+                _ => continue,
+            };
+            let unique = declarations.get(name.as_string()) == Some(&1)
+                && self.interpreter.globals.borrow().get(name).is_err();
+            if let Some(signature) = signature.filter(|_| unique) {
+                signatures.insert(name.as_string().to_string(), signature);
+            }
+        }
+
+        for call in self.global_calls.borrow().iter() {
+            let name = call.callee.as_string();
+            if self.assigned_globals.borrow().contains(name) {
+                continue;
+            }
+            let host;
+            let signature = match signatures.get(name) {
+                Some(signature) => Some(signature),
+                None if !declarations.contains_key(name) => {
+                    host = self.host_signature(&call.callee);
+                    host.as_ref()
+                }
+                None => None,
+            };
+            if let Some(signature) = signature {
+                if signature.arity != call.arguments {
+                    let message = format!("Expected {} arguments but got {}.", signature.arity, call.arguments);
+                    if let Some(mut diagnostic) = LoxResult::resolver_error(&call.paren, "L0210", &message).diagnostic() {
+                        if let Some(declaration) = signature.declaration {
+                            diagnostic = diagnostic.with_label(declaration, &format!("'{name}' declared here"));
+                        }
+                        self.report(diagnostic);
+                    }
+                }
+            }
         }
-        Ok(())
+    }
+
+    fn class_signature(&self, class: &ClassStmt, signatures: &HashMap<String, Signature>) -> Option<Signature> {
+        for method in class.methods.iter() {
+            if let Stmt::Function(method) = method.deref() {
+                if method.name.as_string() == "init" {
+                    return Some(Signature { arity: method.params.len(), declaration: Some(method.name.span), is_class: true });
+                }
+            }
+        }
+        let arity = match class.superclass.as_deref() {
+            None => 0,
+            Some(Expr::Variable(superclass)) => match signatures.get(superclass.name.as_string()) {
+                Some(signature) if signature.is_class => signature.arity,
+                Some(_) => return None,
+                None => match self.host_signature(&superclass.name) {
+                    Some(signature) if signature.is_class => signature.arity,
+                    _ => return None,
+                },
+            },
+            Some(_) => return None,
+        };
+        Some(Signature { arity, declaration: Some(class.name.span), is_class: true })
+    }
+
+    fn host_signature(&self, name: &Token) -> Option<Signature> {
+        // natives, host classes and callables left over from earlier REPL lines
+        let (arity, is_class) = match self.interpreter.globals.borrow().get(name).ok()? {
+            Object::Function(f) => (f.arity(), false),
+            Object::Native(n) => (n.func.arity(), false),
+            Object::Class(c) => (c.arity(), true),
+            _ => return None,
+        };
+        Some(Signature { arity, declaration: None, is_class })
     }
 
     pub fn success(&self) -> bool {
@@ -268,36 +546,181 @@ impl<'a> Resolver<'a> {
     }
 
     fn resolve_stmt(&self, stmt: Rc<Stmt>) -> Result<(), LoxResult> {
+        self.check_stack(stmt.span())?;
         stmt.accept(stmt.clone(), self)
     }
 
+    fn check_stack(&self, span: Span) -> Result<(), LoxResult> {
+        // the parser bounds nesting, but these frames are not the parser's
+        if stack_used(*self.stack_base.borrow()) > self.interpreter.max_stack() {
+            let e = LoxResult::too_much_nesting(DiagnosticKind::Resolution, span);
+            if let Some(diagnostic) = e.diagnostic() {
+                self.report(diagnostic);
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+
     fn begin_scope(&self) {
         self.scopes.borrow_mut().push(RefCell::new(HashMap::new()));
     }
 
     fn end_scope(&self) {
-        self.scopes.borrow_mut().pop();
+        if let Some(scope) = self.scopes.borrow_mut().pop() {
+            if *self.lints.borrow() {
+                self.warn_unused(scope.into_inner());
+            }
+        }
     }
 
-    fn declare(&self, name: &Token) {
-        if let Some(scope) = self.scopes.borrow().last() {
-            if scope.borrow().contains_key(name.as_string()) {
-                self.error(name, "Already a variable with this name in this scope");
+    fn warn_unused(&self, scope: HashMap<String, Local>) {
+        let mut warnings = Vec::new();
+        for (name, local) in scope {
+            if name.starts_with('_') {
+                continue;
+            }
+            let (code, unused) = match local.kind {
+                LocalKind::Variable => ("L0401", format!("Unused local variable '{name}'.")),
+                LocalKind::Parameter => ("L0402", format!("Unused parameter '{name}'.")),
+                LocalKind::Function => ("L0403", format!("Unused local function '{name}'.")),
+                LocalKind::Class | LocalKind::Internal => continue,
+            };
+            if !local.used {
+                warnings.push((local.declaration, code, unused, format!("if this is intentional, prefix it with an underscore: '_{name}'")));
+            } else if !local.captured {
+                for assignment in local.overwritten {
+                    warnings.push((
+                        assignment,
+                        "L0406",
+                        format!("Value assigned to '{name}' is never read."),
+                        "remove the assignment, or use the value before it is overwritten".to_string(),
+                    ));
+                }
+                if let Some((assignment, _)) = local.pending {
+                    warnings.push((
+                        assignment,
+                        "L0406",
+                        format!("Value assigned to '{name}' is never read."),
+                        "remove the assignment, or use the value before the variable goes out of scope".to_string(),
+                    ));
+                }
+            }
+        }
+        warnings.sort_by_key(|(span, _, _, _)| span.start);
+
+        for (span, code, message, help) in warnings {
+            self.report(
+                Diagnostic::new(DiagnosticKind::Resolution, Severity::Warning, Some(code), &message, span.line, None)
+                    .with_span(span)
+                    .with_help(&help),
+            );
+        }
+    }
+
+    fn declare(&self, name: &Token, kind: LocalKind) {
+        let scopes = self.scopes.borrow();
+        let Some((scope, enclosing)) = scopes.split_last() else {
+            self.globals.borrow_mut().insert(name.as_string().into(), name.span);
+            return;
+        };
+
+        if let Some(previous) = scope.borrow().get(name.as_string()) {
+            if let Some(diagnostic) = LoxResult::resolver_error(name, "L0208", "Already a variable with this name in this scope.").diagnostic() {
+                self.report(
+                    diagnostic
+                        .with_label(previous.declaration, &format!("'{}' first declared here", name.as_string()))
+                        .with_help("rename one of the declarations, or assign to the existing variable"),
+                );
             }
-            scope.borrow_mut().insert(name.as_string().into(), false);
+        } else if kind != LocalKind::Parameter && !name.as_string().starts_with('_') {
+            self.warn_shadowing(name, enclosing);
         }
+
+        scope.borrow_mut().insert(
+            name.as_string().into(),
+            Local { defined: false, used: false, kind, declaration: name.span, pending: None, overwritten: Vec::new(), captured: false },
+        );
+    }
+
+    fn warn_shadowing(&self, name: &Token, enclosing: &[RefCell<HashMap<String, Local>>]) {
+        let outer = enclosing
+            .iter()
+            .rev()
+            .find_map(|scope| scope.borrow().get(name.as_string()).map(|l| l.declaration));
+        let (message, declaration) = match outer {
+            Some(declaration) => (format!("Declaration shadows '{}' from an enclosing scope.", name.as_string()), declaration),
+            None => match self.globals.borrow().get(name.as_string()) {
+                Some(declaration) => (format!("Declaration shadows global '{}'.", name.as_string()), *declaration),
+                None => return,
+            },
+        };
+        self.report(
+            Diagnostic::at(DiagnosticKind::Resolution, Severity::Warning, Some("L0405"), &message, name)
+                .with_label(declaration, "shadowed declaration")
+                .with_help("rename the inner declaration if it is meant to be a different variable"),
+        );
     }
 
     fn define(&self, name: &Token) {
         if let Some(scope) = self.scopes.borrow().last() {
-            scope.borrow_mut().insert(name.as_string().into(), true);
+            if let Some(local) = scope.borrow_mut().get_mut(name.as_string()) {
+                local.defined = true;
+            }
+        }
+    }
+
+    fn define_internal(&self, name: &str) {
+        // names bound by the interpreter itself, like 'this' and 'super'
+        if let Some(scope) = self.scopes.borrow().last() {
+            scope.borrow_mut().insert(
+                name.to_string(),
+                Local {
+                    defined: true,
+                    used: true,
+                    kind: LocalKind::Internal,
+                    declaration: Span::default(),
+                    pending: None,
+                    overwritten: Vec::new(),
+                    captured: false,
+                },
+            );
         }
     }
 
-    fn resolve_local(&self, expr: Rc<Expr>, name: &Token) -> Result<(), LoxResult> {
-        for (scope_level, map) in self.scopes.borrow().iter().rev().enumerate() {
-            if map.borrow().contains_key(name.as_string()) {
-                self.interpreter.resolve(expr.clone(), scope_level)?;
+    fn branch(&self) {
+        // stores are only known to be overwritten within one stretch
+        *self.flow.borrow_mut() += 1;
+    }
+
+    fn is_local(&self, name: &Token) -> bool {
+        self.scopes.borrow().iter().any(|scope| scope.borrow().contains_key(name.as_string()))
+    }
+
+    fn resolve_local(&self, expr: Rc<Expr>, name: &Token, is_read: bool) -> Result<(), LoxResult> {
+        let scopes = self.scopes.borrow();
+        let function_scope = *self.function_scope.borrow();
+        for (index, map) in scopes.iter().enumerate().rev() {
+            if let Some(local) = map.borrow_mut().get_mut(name.as_string()) {
+                if is_read {
+                    let recursive = local.kind == LocalKind::Function
+                        && self.defining.borrow().iter().any(|(scope, n)| *scope == index && n == name.as_string());
+                    local.used |= !recursive;
+                    local.pending = None;
+                    for reads in self.loop_reads.borrow_mut().iter_mut() {
+                        reads.insert((index, name.as_string().to_string()));
+                    }
+                } else if index >= function_scope {
+                    let flow = *self.flow.borrow();
+                    if let Some((store, _)) = local.pending.filter(|(_, f)| *f == flow) {
+                        local.overwritten.push(store);
+                    }
+                    local.pending = Some((expr.span(), flow));
+                }
+                // closures may read or write it at any time
+                local.captured |= index < function_scope;
+
+                self.interpreter.resolve(expr.clone(), scopes.len() - 1 - index)?;
                 return Ok(());
             }
         }
@@ -305,29 +728,43 @@ impl<'a> Resolver<'a> {
     }
 
     fn resolve_expr(&self, expr: Rc<Expr>) -> Result<(), LoxResult> {
+        self.check_stack(expr.span())?;
         expr.accept(expr.clone(), self)
     }
 
-    fn resolve_function(&self, function: &FunctionStmt, function_type: FunctionType) -> Result<(), LoxResult> {
+    fn resolve_function(&self, params: &[Token], body: Rc<Vec<Rc<Stmt>>>, function_type: FunctionType) -> Result<(), LoxResult> {
         let enclosing_function = self.current_function.replace(function_type);
+        let enclosing_scope = self.function_scope.replace(self.scopes.borrow().len());
+        // loops don't extend into function bodies
+        let enclosing_loop = self.in_loop.replace(false);
 
         self.begin_scope();
 
-        for param in function.params.iter() {
-            self.declare(param);
+        for param in params {
+            self.declare(param, LocalKind::Parameter);
             self.define(param);
         }
 
-        self.resolve(function.body.clone())?;
+        self.resolve_stmts(body)?;
 
         self.end_scope();
+        self.function_scope.replace(enclosing_scope);
+        self.in_loop.replace(enclosing_loop);
         self.current_function.replace(enclosing_function);
 
         Ok(())
     }
 
-    fn error(&self, token: &Token, message: &str) {
-        self.had_error.replace(true);
-        LoxResult::runtime_error(token, message);
+    fn error(&self, token: &Token, code: &'static str, message: &str) {
+        if let Some(diagnostic) = LoxResult::resolver_error(token, code, message).diagnostic() {
+            self.report(diagnostic);
+        }
+    }
+
+    fn report(&self, diagnostic: Diagnostic) {
+        if diagnostic.is_error() {
+            self.had_error.replace(true);
+        }
+        self.interpreter.diagnostics().push(diagnostic);
     }
 }
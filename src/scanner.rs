@@ -1,3 +1,4 @@
+use crate::diagnostic::*;
 use crate::error::*;
 use crate::token::*;
 use crate::token_type::*;
@@ -8,16 +9,30 @@ pub struct Scanner {
     start: usize,
     current: usize,
     line: usize,
+    line_start: usize,
+    start_line: usize,
+    offsets: Vec<usize>,
+    interpolations: Vec<(usize, Span)>,
+    diagnostics: Diagnostics,
 }
 
 impl Scanner {
-    pub fn new(source: String) -> Scanner {
+    pub fn new(source: String, diagnostics: &Diagnostics) -> Scanner {
+        // byte offset of every character, plus one for the end of the source
+        let mut offsets: Vec<usize> = source.char_indices().map(|(i, _)| i).collect();
+        offsets.push(source.len());
+
         Scanner {
             source: source.chars().collect(),
             tokens: Vec::new(),
             start: 0,
             current: 0,
             line: 1,
+            line_start: 0,
+            start_line: 1,
+            offsets,
+            interpolations: Vec::new(),
+            diagnostics: diagnostics.clone(),
         }
     }
 
@@ -26,15 +41,28 @@ impl Scanner {
 
         while !self.is_at_end() {
             self.start = self.current;
+            self.start_line = self.line;
             match self.scan_token() {
                 Ok(_) => {}
                 Err(e) => {
+                    self.diagnostics.report(&e);
                     had_error = Some(e);
                 }
             }
         }
 
-        self.tokens.push(Token::eof(self.line));
+        if let Some((_, span)) = self.interpolations.last() {
+            // the string carrying this '${' never got its closing '}'
+            if had_error.is_none() {
+                let e = LoxResult::error(*span, "L0002", "Unterminated string interpolation.");
+                self.diagnostics.report(&e);
+                had_error = Some(e);
+            }
+        }
+
+        self.start = self.current;
+        self.start_line = self.line;
+        self.tokens.push(Token::eof(self.current_span()));
 
         match had_error {
             Some(e) => Err(e),
@@ -51,8 +79,27 @@ impl Scanner {
         match c {
             '(' => self.add_token(TokenType::LeftParen),
             ')' => self.add_token(TokenType::RightParen),
-            '{' => self.add_token(TokenType::LeftBrace),
-            '}' => self.add_token(TokenType::RightBrace),
+            '{' => {
+                if let Some((depth, _)) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                self.add_token(TokenType::LeftBrace)
+            }
+            '}' => match self.interpolations.last_mut() {
+                // this brace closes a '${', so the string carries on after it
+                Some((0, _)) => {
+                    self.interpolations.pop();
+                    self.string_part()?;
+                }
+                Some((depth, _)) => {
+                    *depth -= 1;
+                    self.add_token(TokenType::RightBrace)
+                }
+                None => self.add_token(TokenType::RightBrace),
+            },
+            '[' => self.add_token(TokenType::LeftBracket),
+            ':' => self.add_token(TokenType::Colon),
+            ']' => self.add_token(TokenType::RightBracket),
             ',' => self.add_token(TokenType::Comma),
             '.' => self.add_token(TokenType::Dot),
             '-' => self.add_token(TokenType::Minus),
@@ -70,6 +117,8 @@ impl Scanner {
             '=' => {
                 let tok = if self.is_match('=') {
                     TokenType::EqualEqual
+                } else if self.is_match('>') {
+                    TokenType::Arrow
                 } else {
                     TokenType::Equal
                 };
@@ -110,6 +159,7 @@ impl Scanner {
             ' ' | '\t' | '\r' => {}
             '\n' => {
                 self.line += 1;
+                self.line_start = self.current;
             }
             '"' => {
                 self.string()?;
@@ -121,7 +171,7 @@ impl Scanner {
                 if Scanner::is_alpha(Some(c)) {
                     self.identifier();
                 } else {
-                    return Err(LoxResult::error(self.line, "Unexpected character."));
+                    return Err(LoxResult::error(self.current_span(), "L0001", "Unexpected character."));
                 };
             }
         }
@@ -141,7 +191,39 @@ impl Scanner {
     fn add_token_object(&mut self, ttype: TokenType, literal: Option<Object>) {
         let text: String = self.source[self.start..self.current].iter().collect();
         self.tokens
-            .push(Token::new(ttype, text, literal, self.line));
+            .push(Token::new(ttype, text, literal, self.current_span()));
+    }
+
+    fn current_span(&self) -> Span {
+        // location of the lexeme being scanned
+        let line_start = if self.start_line == self.line {
+            self.line_start
+        } else {
+            self.source[..self.start]
+                .iter()
+                .rposition(|&c| c == '\n')
+                .map_or(0, |i| i + 1)
+        };
+        Span {
+            start: self.offsets[self.start],
+            end: self.offsets[self.current],
+            line: self.start_line,
+            column: self.start - line_start + 1,
+            end_line: self.line,
+            end_column: self.current - self.line_start + 1,
+        }
+    }
+
+    fn span_from(&self, start: usize) -> Span {
+        // location of part of a lexeme on the current line, such as an escape
+        Span {
+            start: self.offsets[start],
+            end: self.offsets[self.current],
+            line: self.line,
+            column: start - self.line_start + 1,
+            end_line: self.line,
+            end_column: self.current - self.line_start + 1,
+        }
     }
 
     fn is_match(&mut self, expected: char) -> bool {
@@ -184,6 +266,7 @@ impl Scanner {
         match check {
             "and" => Some(TokenType::And),
             "break" => Some(TokenType::Break),
+            "continue" => Some(TokenType::Continue),
             "class" => Some(TokenType::Class),
             "else" => Some(TokenType::Else),
             "false" => Some(TokenType::False),
@@ -204,27 +287,151 @@ impl Scanner {
     }
 
     fn string(&mut self) -> Result<(), LoxResult> {
+        if self.peek() == Some('"') && self.peek_next() == Some('"') {
+            self.advance();
+            self.advance();
+            return self.raw_string();
+        }
+        self.string_part()
+    }
+
+    fn string_part(&mut self) -> Result<(), LoxResult> {
+        // scans up to the closing quote or the next '${'; the value is built as
+        // we go since escapes don't map 1:1 onto the source
+        let mut value = String::new();
+        let mut error = None;
         while let Some(ch) = self.peek() {
             match ch {
                 '"' => {
                     break;
                 }
+                '$' if self.peek_next() == Some('{') => {
+                    self.advance();
+                    self.advance();
+                    self.interpolations.push((0, self.current_span()));
+                    if let Some(e) = error {
+                        return Err(e);
+                    }
+                    self.add_token_object(TokenType::Interpolation, Some(Object::Str(value)));
+                    return Ok(());
+                }
+                '\\' => {
+                    match self.escape() {
+                        Ok(escaped) => value.push(escaped),
+                        Err(e) => {
+                            // keep the first bad escape but still find the end of the string
+                            error.get_or_insert(e);
+                        }
+                    }
+                    continue;
+                }
                 '\n' => {
                     self.line += 1;
+                    self.line_start = self.current + 1;
                 }
                 _ => {}
             }
+            value.push(ch);
             self.advance();
         }
         if self.is_at_end() {
-            return Err(LoxResult::error(self.line, "Unterminated string."));
+            return Err(LoxResult::error(self.current_span(), "L0002", "Unterminated string."));
         }
         self.advance();
 
-        // Ignore quotes when making string object
-        let value: String = self.source[self.start + 1..self.current - 1]
-            .iter()
-            .collect();
+        if let Some(e) = error {
+            return Err(e);
+        }
+        self.add_token_object(TokenType::String, Some(Object::Str(value)));
+
+        Ok(())
+    }
+
+    fn escape(&mut self) -> Result<char, LoxResult> {
+        // \n \t \r \\ \" \$ \0 and \u{XXXX}; the backslash hasn't been consumed yet
+        let start = self.current;
+        self.advance();
+        let ch = match self.peek() {
+            Some(ch) if ch != '\n' => ch,
+            _ => return Err(LoxResult::error(self.span_from(start), "L0004", "Invalid escape sequence '\\'.")),
+        };
+        self.advance();
+        match ch {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '\\' => Ok('\\'),
+            '"' => Ok('"'),
+            '$' => Ok('$'),
+            'u' => self.unicode_escape(start),
+            _ => Err(LoxResult::error(
+                self.span_from(start),
+                "L0004",
+                &format!("Invalid escape sequence '\\{ch}'."),
+            )),
+        }
+    }
+
+    fn unicode_escape(&mut self, start: usize) -> Result<char, LoxResult> {
+        // \u{1F600}: one to six hex digits naming a Unicode scalar value
+        if !self.is_match('{') {
+            return Err(LoxResult::error(
+                self.span_from(start),
+                "L0005",
+                "Invalid Unicode escape; expected '\\u{' followed by hex digits and '}'.",
+            ));
+        }
+        let digits_start = self.current;
+        while self.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+            self.advance();
+        }
+        let digits: String = self.source[digits_start..self.current].iter().collect();
+        if digits.is_empty() || digits.len() > 6 || !self.is_match('}') {
+            return Err(LoxResult::error(
+                self.span_from(start),
+                "L0005",
+                "Invalid Unicode escape; expected '\\u{' followed by hex digits and '}'.",
+            ));
+        }
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| {
+                LoxResult::error(
+                    self.span_from(start),
+                    "L0005",
+                    &format!("Invalid Unicode code point '{digits}'."),
+                )
+            })
+    }
+
+    fn raw_string(&mut self) -> Result<(), LoxResult> {
+        // """...""" keeps backslashes, newlines and '${' exactly as written
+        loop {
+            match self.peek() {
+                None => {
+                    return Err(LoxResult::error(self.current_span(), "L0002", "Unterminated string."));
+                }
+                Some('"') if self.peek_next() == Some('"') && self.source.get(self.current + 2) == Some(&'"') => {
+                    break;
+                }
+                Some('\n') => {
+                    self.line += 1;
+                    self.line_start = self.current + 1;
+                }
+                _ => {}
+            }
+            self.advance();
+        }
+        self.current += 3;
+
+        // a newline straight after the opening quotes lets templates start on their own line
+        let value: String = self.source[self.start + 3..self.current - 3].iter().collect();
+        let value = match value.strip_prefix("\r\n").or_else(|| value.strip_prefix('\n')) {
+            Some(rest) => rest.to_string(),
+            None => value,
+        };
         self.add_token_object(TokenType::String, Some(Object::Str(value)));
 
         Ok(())
@@ -263,11 +470,12 @@ impl Scanner {
     }
 
     fn block_comment(&mut self) -> Result<(), LoxResult> {
-        let mut nest_count: u8 = 1;
+        let mut nest_count: usize = 1;
         while let Some(ch) = self.peek() {
             match ch {
                 '\n' => {
                     self.line += 1;
+                    self.line_start = self.current + 1;
                 }
                 '/' if self.peek_next() == Some('*') => {
                     nest_count += 1;
@@ -286,7 +494,7 @@ impl Scanner {
         }
 
         if self.is_at_end() {
-            return Err(LoxResult::error(self.line, "Unterminated block comment."));
+            return Err(LoxResult::error(self.current_span(), "L0003", "Unterminated block comment."));
         }
         self.advance();
         self.advance();
@@ -0,0 +1,17 @@
+// The parser, resolver and interpreter all recurse on the shape of the program,
+// so each of them watches how much native stack it has used since it started.
+
+#[inline(never)]
+pub fn stack_address() -> usize {
+    // the address of a local approximates the current stack pointer
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
+
+pub fn stack_used(base: Option<usize>) -> usize {
+    // bytes used since base was taken, whichever way the stack grows
+    match base {
+        Some(base) => base.abs_diff(stack_address()),
+        None => 0,
+    }
+}
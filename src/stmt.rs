@@ -8,6 +8,7 @@ pub enum Stmt {
     Break(Rc<BreakStmt>),
     Block(Rc<BlockStmt>),
     Class(Rc<ClassStmt>),
+    Continue(Rc<ContinueStmt>),
     Expression(Rc<ExpressionStmt>),
     Function(Rc<FunctionStmt>),
     If(Rc<IfStmt>),
@@ -23,6 +24,7 @@ impl PartialEq for Stmt {
             (Stmt::Break(a), Stmt::Break(b)) => Rc::ptr_eq(a, b),
             (Stmt::Block(a), Stmt::Block(b)) => Rc::ptr_eq(a, b),
             (Stmt::Class(a), Stmt::Class(b)) => Rc::ptr_eq(a, b),
+            (Stmt::Continue(a), Stmt::Continue(b)) => Rc::ptr_eq(a, b),
             (Stmt::Expression(a), Stmt::Expression(b)) => Rc::ptr_eq(a, b),
             (Stmt::Function(a), Stmt::Function(b)) => Rc::ptr_eq(a, b),
             (Stmt::If(a), Stmt::If(b)) => Rc::ptr_eq(a, b),
@@ -49,6 +51,9 @@ impl Hash for Stmt {
             Stmt::Class(a) => {
                 hasher.write_usize(Rc::as_ptr(a) as usize);
             }
+            Stmt::Continue(a) => {
+                hasher.write_usize(Rc::as_ptr(a) as usize);
+            }
             Stmt::Expression(a) => {
                 hasher.write_usize(Rc::as_ptr(a) as usize);
             }
@@ -80,6 +85,7 @@ impl Stmt {
             Stmt::Break(v) => stmt_visitor.visit_break_stmt(wrapper, v),
             Stmt::Block(v) => stmt_visitor.visit_block_stmt(wrapper, v),
             Stmt::Class(v) => stmt_visitor.visit_class_stmt(wrapper, v),
+            Stmt::Continue(v) => stmt_visitor.visit_continue_stmt(wrapper, v),
             Stmt::Expression(v) => stmt_visitor.visit_expression_stmt(wrapper, v),
             Stmt::Function(v) => stmt_visitor.visit_function_stmt(wrapper, v),
             Stmt::If(v) => stmt_visitor.visit_if_stmt(wrapper, v),
@@ -89,61 +95,95 @@ impl Stmt {
             Stmt::While(v) => stmt_visitor.visit_while_stmt(wrapper, v),
         }
     }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::Break(v) => v.span,
+            Stmt::Block(v) => v.span,
+            Stmt::Class(v) => v.span,
+            Stmt::Continue(v) => v.span,
+            Stmt::Expression(v) => v.span,
+            Stmt::Function(v) => v.span,
+            Stmt::If(v) => v.span,
+            Stmt::Print(v) => v.span,
+            Stmt::Return(v) => v.span,
+            Stmt::Var(v) => v.span,
+            Stmt::While(v) => v.span,
+        }
+    }
 }
 
 pub struct BreakStmt {
     pub token: Token,
+    pub span: Span,
 }
 
 pub struct BlockStmt {
     pub statements: Rc<Vec<Rc<Stmt>>>,
+    pub span: Span,
 }
 
 pub struct ClassStmt {
     pub name: Token,
     pub superclass: Option<Rc<Expr>>,
     pub methods: Rc<Vec<Rc<Stmt>>>,
+    pub span: Span,
+}
+
+pub struct ContinueStmt {
+    pub token: Token,
+    pub span: Span,
 }
 
 pub struct ExpressionStmt {
     pub expression: Rc<Expr>,
+    pub span: Span,
 }
 
 pub struct FunctionStmt {
     pub name: Token,
     pub params: Rc<Vec<Token>>,
     pub body: Rc<Vec<Rc<Stmt>>>,
+    pub span: Span,
 }
 
 pub struct IfStmt {
     pub condition: Rc<Expr>,
     pub then_branch: Rc<Stmt>,
     pub else_branch: Option<Rc<Stmt>>,
+    pub span: Span,
 }
 
 pub struct PrintStmt {
+    pub keyword: Token,
     pub expression: Rc<Expr>,
+    pub span: Span,
 }
 
 pub struct ReturnStmt {
     pub keyword: Token,
     pub value: Option<Rc<Expr>>,
+    pub span: Span,
 }
 
 pub struct VarStmt {
     pub name: Token,
     pub initializer: Option<Rc<Expr>>,
+    pub span: Span,
 }
 
 pub struct WhileStmt {
     pub condition: Rc<Expr>,
     pub body: Rc<Stmt>,
+    pub increment: Option<Rc<Expr>>,
+    pub span: Span,
 }
 
 pub trait StmtVisitor<T> {
     fn visit_break_stmt(&self, wrapper: Rc<Stmt>, stmt: &BreakStmt) -> Result<T, LoxResult>;
     fn visit_block_stmt(&self, wrapper: Rc<Stmt>, stmt: &BlockStmt) -> Result<T, LoxResult>;
     fn visit_class_stmt(&self, wrapper: Rc<Stmt>, stmt: &ClassStmt) -> Result<T, LoxResult>;
+    fn visit_continue_stmt(&self, wrapper: Rc<Stmt>, stmt: &ContinueStmt) -> Result<T, LoxResult>;
     fn visit_expression_stmt(&self, wrapper: Rc<Stmt>, stmt: &ExpressionStmt) -> Result<T, LoxResult>;
     fn visit_function_stmt(&self, wrapper: Rc<Stmt>, stmt: &FunctionStmt) -> Result<T, LoxResult>;
     fn visit_if_stmt(&self, wrapper: Rc<Stmt>, stmt: &IfStmt) -> Result<T, LoxResult>;
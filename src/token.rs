@@ -1,16 +1,20 @@
+use crate::error::*;
 use crate::token_type::*;
 // use crate::callable::*;
 use crate::native_functions::*;
 use crate::lox_instance::*;
 use crate::lox_class::*;
 use crate::lox_function::*;
+use crate::lox_map::*;
+use std::cell::RefCell;
 use std::cmp::*;
 use std::fmt;
 use std::rc::Rc;
 use std::ops::*;
 
+const MAX_STRING_LEN: usize = 1 << 28;
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone)]
 pub enum Object {
     Num(f64),
     Str(String),
@@ -19,13 +23,84 @@ pub enum Object {
     Class(Rc<LoxClass>),
     Instance(Rc<LoxInstance>),
     Native(Rc<LoxNative>),
+    List(Rc<RefCell<Vec<Object>>>),
+    Map(Rc<RefCell<LoxMap>>),
     Nil,
-    ErrorMessage(String),
+    ErrorMessage(ErrorMessage),
+}
+
+impl PartialEq for Object {
+    fn eq(&self, other: &Self) -> bool {
+        // collections compare by identity, which also keeps cycles finite
+        match (self, other) {
+            (Object::Num(a), Object::Num(b)) => a == b,
+            (Object::Str(a), Object::Str(b)) => a == b,
+            (Object::Bool(a), Object::Bool(b)) => a == b,
+            (Object::Function(a), Object::Function(b)) => a == b,
+            (Object::Class(a), Object::Class(b)) => a == b,
+            (Object::Instance(a), Object::Instance(b)) => a == b,
+            (Object::Native(a), Object::Native(b)) => a == b,
+            (Object::List(a), Object::List(b)) => Rc::ptr_eq(a, b),
+            (Object::Map(a), Object::Map(b)) => Rc::ptr_eq(a, b),
+            (Object::Nil, Object::Nil) => true,
+            (Object::ErrorMessage(a), Object::ErrorMessage(b)) => a == b,
+            _ => false,
+        }
+    }
 }
 
 impl fmt::Display for Object {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_nested(f, &mut Vec::new())
+    }
+}
+
+impl Object {
+    pub fn list(elements: Vec<Object>) -> Object {
+        Object::List(Rc::new(RefCell::new(elements)))
+    }
+
+    pub fn map(map: LoxMap) -> Object {
+        Object::Map(Rc::new(RefCell::new(map)))
+    }
+
+    fn fmt_nested(&self, f: &mut fmt::Formatter, seen: &mut Vec<*const ()>) -> fmt::Result {
         match self {
+            Object::List(list) => {
+                // a list that contains itself prints as [...] the second time
+                let ptr = Rc::as_ptr(list) as *const ();
+                if seen.contains(&ptr) {
+                    return write!(f, "[...]");
+                }
+                seen.push(ptr);
+                write!(f, "[")?;
+                for (i, element) in list.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    element.fmt_element(f, seen)?;
+                }
+                seen.pop();
+                write!(f, "]")
+            }
+            Object::Map(map) => {
+                let ptr = Rc::as_ptr(map) as *const ();
+                if seen.contains(&ptr) {
+                    return write!(f, "{{...}}");
+                }
+                seen.push(ptr);
+                write!(f, "{{")?;
+                for (i, (key, value)) in map.borrow().entries().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    key.fmt_element(f, seen)?;
+                    write!(f, ": ")?;
+                    value.fmt_element(f, seen)?;
+                }
+                seen.pop();
+                write!(f, "}}")
+            }
             Object::Num(n) => write!(f, "{n}"),
             Object::Str(s) => write!(f, "{s}"),
             Object::Bool(b) => {
@@ -40,7 +115,23 @@ impl fmt::Display for Object {
             Object::Instance(i) => write!(f, "{}", i),
             Object::Native(n) => write!(f, "{}", n),
             Object::Nil => write!(f, "nil"),
-            Object::ErrorMessage(_) => panic!("Do not print upon error."),
+            Object::ErrorMessage(e) => write!(f, "{e}"),
+        }
+    }
+
+    pub fn repr(&self) -> String {
+        // the value as it would appear inside a list or map
+        match self {
+            Object::Str(s) => format!("{s:?}"),
+            value => value.to_string(),
+        }
+    }
+
+    fn fmt_element(&self, f: &mut fmt::Formatter, seen: &mut Vec<*const ()>) -> fmt::Result {
+        // strings inside collections are quoted so "1" and 1 look different
+        match self {
+            Object::Str(s) => write!(f, "{s:?}"),
+            value => value.fmt_nested(f, seen),
         }
     }
 }
@@ -51,7 +142,7 @@ impl Sub for Object {
     fn sub(self, other: Self) -> Object {
         match (self, other) {
             (Object::Num(left), Object::Num(right)) => Object::Num(left - right),
-            _ => Object::ErrorMessage("Operands must be numbers.".to_string()),
+            _ => Object::ErrorMessage(ErrorMessage::new("L0301", "Operands must be numbers.")),
         }
     }
 }
@@ -63,12 +154,12 @@ impl Div for Object {
         match (self, other) {
             (Object::Num(left), Object::Num(right)) => {
                 if right == 0.0 {
-                    Object::ErrorMessage("Cannot divide by zero.".to_string())
+                    Object::ErrorMessage(ErrorMessage::new("L0302", "Cannot divide by zero."))
                 } else {
                     Object::Num(left / right)
                 }
             }
-            _ => Object::ErrorMessage("Operands must be numbers.".to_string()),
+            _ => Object::ErrorMessage(ErrorMessage::new("L0301", "Operands must be numbers.")),
         }
     }
 }
@@ -79,10 +170,17 @@ impl Mul for Object {
     fn mul(self, other: Self) -> Object {
         match (self, other) {
             (Object::Num(left), Object::Num(right)) => Object::Num(left * right),
-            (Object::Str(s), Object::Num(n)) => Object::Str(s.repeat(n as usize)),
-            _ => Object::ErrorMessage(
-                "Operands must be numbers or a string and a number.".to_string(),
-            ),
+            (Object::Str(s), Object::Num(n)) => {
+                // refuse counts that would exhaust memory instead of aborting
+                match s.len().checked_mul(n as usize) {
+                    Some(len) if len <= MAX_STRING_LEN => Object::Str(s.repeat(n as usize)),
+                    _ => Object::ErrorMessage(ErrorMessage::new("L0312", "String is too long.")),
+                }
+            }
+            _ => Object::ErrorMessage(ErrorMessage::new(
+                "L0301",
+                "Operands must be numbers or a string and a number.",
+            )),
         }
     }
 }
@@ -96,7 +194,7 @@ impl Add for Object {
             (Object::Str(left), Object::Str(right)) => Object::Str(format!("{}{}", left, right)),
             (Object::Str(left), Object::Num(right)) => Object::Str(format!("{}{}", left, right)),
             (Object::Num(left), Object::Str(right)) => Object::Str(format!("{}{}", left, right)),
-            _ => Object::ErrorMessage("Operants must be numbers or strings.".to_string()),
+            _ => Object::ErrorMessage(ErrorMessage::new("L0301", "Operands must be numbers or strings.")),
         }
     }
 }
@@ -120,7 +218,7 @@ impl PartialOrd for Object {
 impl Object {
     pub fn compare(left: Object, operator: Token, right: Object) -> Object {
         if !Self::are_num_objects(left.clone(), right.clone()) {
-            Object::ErrorMessage("Operands must be numbers.".to_string())
+            Object::ErrorMessage(ErrorMessage::new("L0301", "Operands must be numbers."))
         } else {
             let first = Self::deconstruct_num_object(left).unwrap();
             let second = Self::deconstruct_num_object(right).unwrap();
@@ -129,7 +227,7 @@ impl Object {
                 TokenType::GreaterEqual => Object::Bool(first >= second),
                 TokenType::Less => Object::Bool(first < second),
                 TokenType::LessEqual => Object::Bool(first <= second),
-                _ => Object::ErrorMessage("Invalid comparator".to_string()),
+                _ => Object::ErrorMessage(ErrorMessage::new("L0320", "Invalid comparator")),
             }
         }
     }
@@ -148,21 +246,50 @@ impl Object {
         }
     }
 }
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+pub struct Span {
+    // byte offsets into the source, end exclusive
+    pub start: usize,
+    pub end: usize,
+    // 1-based line and column (in characters) of the first character
+    pub line: usize,
+    pub column: usize,
+    // line and column just past the last character
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Span {
+    pub fn to(&self, other: Span) -> Span {
+        // span running from the start of self to the end of other
+        Span {
+            start: self.start,
+            end: other.end,
+            line: self.line,
+            column: self.column,
+            end_line: other.end_line,
+            end_column: other.end_column,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     ttype: TokenType,
     lexeme: String,
     pub literal: Option<Object>,
     pub line: usize,
+    pub span: Span,
 }
 
 impl Token {
-    pub fn new(ttype: TokenType, lexeme: String, literal: Option<Object>, line: usize) -> Token {
+    pub fn new(ttype: TokenType, lexeme: String, literal: Option<Object>, span: Span) -> Token {
         Token {
             ttype,
             lexeme,
             literal,
-            line,
+            line: span.line,
+            span,
         }
     }
 
@@ -184,15 +311,17 @@ impl Token {
             lexeme: self.lexeme.to_string(),
             literal: self.literal.clone(),
             line: self.line,
+            span: self.span,
         }
     }
 
-    pub fn eof(line: usize) -> Token {
+    pub fn eof(span: Span) -> Token {
         Token {
             ttype: TokenType::Eof,
             lexeme: "".to_string(),
             literal: None,
-            line,
+            line: span.line,
+            span,
         }
     }
 }
@@ -5,6 +5,9 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
+    Colon,
     Comma,
     Dot,
     Minus,
@@ -22,15 +25,18 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    Arrow,
 
     // Literals
     Identifier,
     String,
+    Interpolation,
     Number,
 
     // Keywords
     And,
     Break,
+    Continue,
     Class,
     Else,
     False,
@@ -0,0 +1,83 @@
+use lox_ast::{Lox, Object, RunError};
+
+fn arity_errors(lox: &mut Lox, source: &str) -> Vec<(String, Vec<String>)> {
+    lox.interpreter().set_output(std::io::sink());
+    let _ = lox.run(source);
+    lox.diagnostics()
+        .take()
+        .into_iter()
+        .filter(|d| d.code == Some("L0210"))
+        .map(|d| (d.message, d.labels.into_iter().map(|label| label.message).collect()))
+        .collect()
+}
+
+fn check(source: &str) -> Vec<(String, Vec<String>)> {
+    arity_errors(&mut Lox::new(), source)
+}
+
+fn error(message: &str, labels: &[&str]) -> (String, Vec<String>) {
+    (message.to_string(), labels.iter().map(|l| l.to_string()).collect())
+}
+
+#[test]
+fn calls_to_top_level_functions_are_checked_before_running() {
+    let mut lox = Lox::new();
+    let found = arity_errors(&mut lox, "print \"ran\"; fun f(a) { return a; } f();");
+    assert_eq!(found, [error("Expected 1 arguments but got 0.", &["'f' declared here"])]);
+    assert!(matches!(lox.run("fun f(a) { return a; } f(1, 2);"), Err(RunError::Compile)));
+}
+
+#[test]
+fn class_calls_are_checked_against_init() {
+    let found = check("class A { init(a, b) {} } A(1);");
+    assert_eq!(found, [error("Expected 2 arguments but got 1.", &["'A' declared here"])]);
+
+    let found = check("class A {} A(1);");
+    assert_eq!(found, [error("Expected 0 arguments but got 1.", &["'A' declared here"])]);
+}
+
+#[test]
+fn class_calls_are_checked_against_an_inherited_init() {
+    let found = check("class A { init(a) {} } class B < A {} class C < B {} C();");
+    assert_eq!(found, [error("Expected 1 arguments but got 0.", &["'C' declared here"])]);
+}
+
+#[test]
+fn calls_to_host_natives_are_checked() {
+    let mut lox = Lox::new();
+    lox.interpreter().define_native("add", 2, |_| Ok(Object::Nil));
+    let found = arity_errors(&mut lox, "add(1);");
+    assert_eq!(found, [error("Expected 2 arguments but got 1.", &[])]);
+
+    let found = arity_errors(&mut lox, "add(1, 2); clock(1);");
+    assert_eq!(found, [error("Expected 0 arguments but got 1.", &[])]);
+}
+
+#[test]
+fn names_that_may_be_rebound_are_not_checked() {
+    // reassigned, before or after the call
+    assert_eq!(check("fun f(a) {} f = clock; f();"), []);
+    assert_eq!(check("fun f(a) {} fun g() { f = clock; } g(); f();"), []);
+    // declared twice
+    assert_eq!(check("fun f(a) {} fun f() {} f();"), []);
+    assert_eq!(check("fun f(a) {} var f = clock; f();"), []);
+    // a local of the same name is what gets called
+    assert_eq!(check("fun f(a) {} fun g(f) { f(); } g(clock);"), []);
+    assert_eq!(check("fun f(a) {} { var f = clock; f(); }"), []);
+    // a subclass of a class that isn't trusted
+    assert_eq!(check("class A {} class A { init(a) {} } class B < A {} B(1);"), []);
+}
+
+#[test]
+fn declarations_replacing_existing_globals_are_not_checked() {
+    let mut lox = Lox::new();
+    lox.interpreter().define_native("add", 2, |_| Ok(Object::Nil));
+    assert_eq!(arity_errors(&mut lox, "fun add(a) { return a; } add(1);"), []);
+
+    // an earlier run's function may still be what the call reaches
+    let mut lox = Lox::new();
+    assert_eq!(arity_errors(&mut lox, "fun f(a, b) {}"), []);
+    assert_eq!(arity_errors(&mut lox, "f(1, 2); fun f(a) {}"), []);
+    assert_eq!(arity_errors(&mut lox, "f(1);"), []);
+    assert_eq!(arity_errors(&mut lox, "f(1, 2);"), [error("Expected 1 arguments but got 2.", &[])]);
+}
@@ -0,0 +1,50 @@
+use lox_ast::interpreter::DEFAULT_MAX_CALL_DEPTH;
+use std::fs::File;
+use std::process::{Command, Output};
+
+fn rlox(name: &str, source: &str) -> Output {
+    let path = std::env::temp_dir().join(format!("rlox-{}-{name}.lox", std::process::id()));
+    std::fs::write(&path, source).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_rlox")).arg(&path).output().unwrap();
+    let _ = std::fs::remove_file(&path);
+    output
+}
+
+#[test]
+fn scripts_can_recurse_to_the_call_depth_limit() {
+    // the body nests a little so each call costs more than a bare return
+    let count = "fun count(n) { if (n == 0) return 0; { { while (true) { return 1 + count(n - 1); } } } }";
+    let deepest = DEFAULT_MAX_CALL_DEPTH - 1;
+
+    let output = rlox("deepest", &format!("{count} print count({deepest});"));
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), format!("{deepest}\n"));
+
+    // one more call is refused by the depth limit, not by the stack guard
+    let output = rlox("too-deep", &format!("{count} print count({});", deepest + 1));
+    assert_eq!(output.status.code(), Some(70));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("error[L0309]: Stack overflow."));
+}
+
+#[test]
+fn warnings_are_printed_before_the_script_runs() {
+    // stdout and stderr share one file so their order is kept
+    let path = std::env::temp_dir().join(format!("rlox-{}-warn.lox", std::process::id()));
+    let log = std::env::temp_dir().join(format!("rlox-{}-warn.log", std::process::id()));
+    std::fs::write(&path, "fun f() { var unused = 1; } print \"ran\";\n").unwrap();
+    let out = File::create(&log).unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_rlox"))
+        .arg(&path)
+        .stdout(out.try_clone().unwrap())
+        .stderr(out)
+        .status()
+        .unwrap();
+    let printed = std::fs::read_to_string(&log).unwrap();
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(&log);
+
+    assert!(status.success());
+    let warning = printed.find("warning[L0401]").expect(&printed);
+    let output = printed.find("ran\n").expect(&printed);
+    assert!(warning < output, "{printed}");
+}
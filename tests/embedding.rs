@@ -0,0 +1,302 @@
+use lox_ast::token::Token;
+use lox_ast::token_type::TokenType;
+use lox_ast::{Lox, LoxClass, Object, RunError};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// a Lox whose print output is collected into a shared string
+fn capture() -> (Lox, Rc<RefCell<String>>) {
+    let lox = Lox::new();
+    let output = Rc::new(RefCell::new(String::new()));
+    let sink = Rc::clone(&output);
+    lox.interpreter().set_output_callback(move |text| sink.borrow_mut().push_str(text));
+    (lox, output)
+}
+
+#[test]
+fn output_callback_receives_printed_lines() {
+    let (mut lox, output) = capture();
+    lox.run("print 1 + 2; print \"two\"; print nil;").unwrap();
+    assert_eq!(*output.borrow(), "3\ntwo\nnil\n");
+}
+
+#[test]
+fn output_callback_keeps_output_from_before_an_error() {
+    let (mut lox, output) = capture();
+    assert!(lox.run("print \"before\"; print 1 / nil; print \"after\";").is_err());
+    assert_eq!(*output.borrow(), "before\n");
+}
+
+#[test]
+fn output_can_be_redirected_to_a_writer() {
+    #[derive(Clone)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl std::io::Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let buffer = Shared(Rc::new(RefCell::new(Vec::new())));
+    let mut lox = Lox::new();
+    lox.interpreter().set_output(buffer.clone());
+    lox.run("print \"hi\";").unwrap();
+    assert_eq!(*buffer.0.borrow(), b"hi\n");
+}
+
+fn messages(lox: &Lox) -> Vec<String> {
+    lox.diagnostics().take().into_iter().map(|d| d.message).collect()
+}
+
+#[test]
+fn native_functions_are_callable_from_scripts() {
+    let (mut lox, output) = capture();
+    lox.interpreter().define_native("add", 2, |args| match (&args[0], &args[1]) {
+        (Object::Num(a), Object::Num(b)) => Ok(Object::Num(a + b)),
+        _ => Err("add needs two numbers.".to_string()),
+    });
+    lox.run("print add(2, 3); var f = add; print f(f(1, 1), 1);").unwrap();
+    assert_eq!(*output.borrow(), "5\n3\n");
+}
+
+#[test]
+fn native_function_errors_are_runtime_errors_at_the_call() {
+    let (mut lox, _) = capture();
+    lox.interpreter().define_native("fail", 0, |_| Err("Host said no.".to_string()));
+    assert!(matches!(lox.run("\nfail();"), Err(RunError::Runtime)));
+    let diagnostics = lox.diagnostics().take();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "Host said no.");
+    assert_eq!(diagnostics[0].line, 2);
+}
+
+#[test]
+fn native_function_arity_is_checked() {
+    let (mut lox, _) = capture();
+    lox.interpreter().define_native("one", 1, |args| Ok(args[0].clone()));
+    assert!(lox.run("one(1, 2);").is_err());
+    assert_eq!(messages(&lox), ["Expected 1 arguments but got 2."]);
+}
+
+// a host class whose instances carry a Rust counter as their payload
+fn counter_class() -> LoxClass {
+    let mut class = LoxClass::native("Counter");
+    class.define_method("init", 1, |this, args| match args[0] {
+        Object::Num(start) if start >= 0.0 => {
+            this.set_payload(start as u64);
+            Ok(Object::Nil)
+        }
+        _ => Err("Counter needs a non-negative start.".to_string()),
+    });
+    class.define_method("bump", 0, |this, _| {
+        let mut count = this.payload_mut::<u64>().ok_or("Counter is not initialized.")?;
+        *count += 1;
+        Ok(Object::Num(*count as f64))
+    });
+    class.define_method("get", 0, |this, _| {
+        let count = this.payload::<u64>().ok_or("Counter is not initialized.")?;
+        Ok(Object::Num(*count as f64))
+    });
+    class
+}
+
+#[test]
+fn native_classes_keep_a_payload_per_instance() {
+    let (mut lox, output) = capture();
+    lox.interpreter().define_class(counter_class());
+    lox.run(
+        "var a = Counter(10); var b = Counter(0);
+         a.bump(); a.bump(); b.bump();
+         print a.get(); print b.get();
+         a.label = \"fields still work\"; print a.label;",
+    )
+    .unwrap();
+    assert_eq!(*output.borrow(), "12\n1\nfields still work\n");
+}
+
+#[test]
+fn native_initializer_errors_are_runtime_errors() {
+    let (mut lox, output) = capture();
+    lox.interpreter().define_class(counter_class());
+    assert!(matches!(lox.run("var c = Counter(-1); print c;"), Err(RunError::Runtime)));
+    assert_eq!(messages(&lox), ["Counter needs a non-negative start."]);
+    assert_eq!(*output.borrow(), "");
+}
+
+#[test]
+fn script_classes_can_extend_native_classes() {
+    let (mut lox, output) = capture();
+    lox.interpreter().define_class(counter_class());
+    lox.run(
+        "class Stepper < Counter {
+           init(start, step) {
+             super.init(start);
+             this.step = step;
+           }
+           advance() {
+             for (var i = 0; i < this.step; i = i + 1) this.bump();
+             return this.get();
+           }
+         }
+         var s = Stepper(1, 3);
+         print s.advance();
+         print s.bump();",
+    )
+    .unwrap();
+    assert_eq!(*output.borrow(), "4\n5\n");
+}
+
+#[test]
+fn payloads_can_be_read_back_by_the_host() {
+    let (mut lox, _) = capture();
+    lox.interpreter().define_class(counter_class());
+    lox.run("var c = Counter(41); c.bump();").unwrap();
+    let name = Token::new(TokenType::Identifier, "c".to_string(), None, Default::default());
+    let Ok(Object::Instance(instance)) = lox.interpreter().globals.borrow().get(&name) else {
+        panic!("'c' should be an instance");
+    };
+    assert_eq!(instance.payload::<u64>().as_deref(), Some(&42));
+    assert!(instance.payload::<String>().is_none());
+}
+
+#[test]
+fn fuel_stops_runaway_scripts() {
+    let (mut lox, _) = capture();
+    lox.interpreter().set_fuel(Some(1000));
+    assert!(matches!(lox.run("while (true) {}"), Err(RunError::Runtime)));
+    assert_eq!(messages(&lox), ["Execution budget exhausted."]);
+    assert_eq!(lox.interpreter().remaining_fuel(), Some(0));
+    assert_eq!(lox.interpreter().fuel_consumed(), 1000);
+}
+
+#[test]
+fn setting_fuel_restarts_the_consumed_count() {
+    let (mut lox, output) = capture();
+    lox.interpreter().set_fuel(Some(1000));
+    let _ = lox.run("while (true) {}");
+
+    lox.interpreter().set_fuel(Some(1000));
+    lox.run("print 1;").unwrap();
+    let used = lox.interpreter().fuel_consumed();
+    assert!(used > 0 && used < 10, "consumed {used}");
+    assert_eq!(lox.interpreter().remaining_fuel(), Some(1000 - used));
+    assert_eq!(*output.borrow(), "1\n");
+}
+
+#[test]
+fn a_cancelled_run_does_not_stop_the_next_one() {
+    let (mut lox, output) = capture();
+    let handle = lox.interpreter().cancel_handle();
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        handle.cancel();
+    });
+    assert!(matches!(lox.run("while (true) {}"), Err(RunError::Interrupted)));
+    canceller.join().unwrap();
+
+    lox.run("print 1;").unwrap();
+    assert_eq!(*output.borrow(), "1\n");
+}
+
+#[test]
+fn a_cancel_before_the_run_starts_is_not_lost() {
+    let (mut lox, output) = capture();
+    lox.interpreter().cancel_handle().cancel();
+    assert!(matches!(lox.run("print 1;"), Err(RunError::Interrupted)));
+    assert!(!lox.interpreter().cancel_handle().is_cancelled());
+
+    lox.run("print 2;").unwrap();
+    assert_eq!(*output.borrow(), "2\n");
+}
+
+#[test]
+fn failing_output_is_an_io_error() {
+    struct Broken;
+
+    impl std::io::Write for Broken {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::new(std::io::ErrorKind::BrokenPipe, "pipe closed"))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut lox = Lox::new();
+    lox.interpreter().set_output(Broken);
+    let error = lox.run("print 1;").unwrap_err();
+    assert!(matches!(error, RunError::Io(_)));
+    assert_eq!(error.exit_code(), 74);
+    assert_eq!(messages(&lox), ["Could not write output: pipe closed."]);
+}
+
+#[test]
+fn only_errors_raised_by_lox_itself_have_codes() {
+    let (mut lox, _) = capture();
+    // a host message that reads like a built-in one still has no code
+    lox.interpreter().define_native("check", 1, |_| Err("Argument must be a number.".to_string()));
+    assert!(lox.run("check(1);").is_err());
+    let host = lox.diagnostics().take();
+    assert_eq!(host[0].message, "Argument must be a number.");
+    assert_eq!(host[0].code, None);
+
+    assert!(lox.run("upper(1);").is_err());
+    assert_eq!(lox.diagnostics().take()[0].code, Some("L0316"));
+
+    lox.interpreter().set_output(std::io::sink());
+    assert!(lox.run("print 1 + nil;").is_err());
+    assert_eq!(lox.diagnostics().take()[0].code, Some("L0301"));
+}
+
+#[test]
+fn empty_interpolation_is_a_missing_expression() {
+    let (mut lox, output) = capture();
+    assert!(matches!(lox.run("print \"a${}b\";"), Err(RunError::Compile)));
+    assert_eq!(messages(&lox), ["Expect expression."]);
+
+    // a nested string right after '${' is still an expression
+    lox.run("print \"${\"${1}\"}\";").unwrap();
+    assert_eq!(*output.borrow(), "1\n");
+}
+
+#[test]
+fn errors_are_only_deduplicated_within_a_run() {
+    let (mut lox, _) = capture();
+    assert!(matches!(lox.run("print x;"), Err(RunError::Runtime)));
+    assert!(matches!(lox.run("print y;"), Err(RunError::Runtime)));
+    assert_eq!(messages(&lox), ["Undefined variable 'x'.", "Undefined variable 'y'."]);
+}
+
+#[test]
+fn runtime_errors_carry_a_backtrace() {
+    let (mut lox, _) = capture();
+    let source = "\
+class Box {
+  open() {
+    return nope;
+  }
+}
+fun unpack(box) {
+  return box.open();
+}
+print \"start\";
+unpack(Box());
+";
+    assert!(matches!(lox.run(source), Err(RunError::Runtime)));
+    let trace = |lox: &Lox| -> Vec<(String, usize)> {
+        let diagnostics = lox.diagnostics().take();
+        diagnostics[0].trace.iter().map(|f| (f.function.clone(), f.line)).collect()
+    };
+    assert_eq!(trace(&lox), [("open".to_string(), 3), ("unpack".to_string(), 7), ("<script>".to_string(), 10)]);
+
+    // the frames of the failed run are gone by the next one
+    assert!(matches!(lox.run("\nprint nope;"), Err(RunError::Runtime)));
+    assert_eq!(trace(&lox), [("<script>".to_string(), 2)]);
+}
@@ -0,0 +1,161 @@
+use lox_ast::{Lox, RunError};
+use std::fs;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+
+const CASES: usize = 10_000;
+
+const FRAGMENTS: &[&str] = &[
+    "var", "fun", "class", "<", "super", "this", "return", "break", "continue", "if", "else", "while", "for",
+    "print", "and", "or", "nil", "true", "false", "(", ")", "{", "}", "[", "]", ":", ",", ".", ";", "=", "==",
+    "!=", "!", "+", "-", "*", "/", ">", ">=", "<=", "=>", "a", "b", "init", "clock", "0", "1", "2.5",
+    "1e", "\"s\"", "\"", "\\", "\\u{", "\"\"\"", "${", "\"${", "//", "#", "\u{e9}", "\n", "\t",
+];
+
+// xorshift64; a fixed seed keeps failures reproducible without extra dependencies
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+fn run(source: &str) {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut lox = Lox::new();
+        lox.interpreter().set_output(io::sink());
+        lox.interpreter().set_fuel(Some(20_000));
+        let _ = lox.run(source);
+    }));
+    assert!(result.is_ok(), "interpreter panicked on:\n{source}");
+}
+
+#[test]
+fn random_token_streams_do_not_panic() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    for _ in 0..CASES {
+        let len = 1 + rng.below(40);
+        let source: Vec<&str> = (0..len).map(|_| FRAGMENTS[rng.below(FRAGMENTS.len())]).collect();
+        run(&source.join(" "));
+    }
+}
+
+#[test]
+fn mutated_sample_scripts_do_not_panic() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    let mut samples = Vec::new();
+    for entry in fs::read_dir("tests").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|e| e == "lox") {
+            samples.push(fs::read_to_string(path).unwrap());
+        }
+    }
+
+    for _ in 0..CASES {
+        let sample = &samples[rng.below(samples.len())];
+        let mut words: Vec<&str> = sample.split_whitespace().collect();
+        for _ in 0..1 + rng.below(4) {
+            if words.is_empty() {
+                break;
+            }
+            let at = rng.below(words.len());
+            match rng.below(3) {
+                0 => {
+                    words.remove(at);
+                }
+                1 => words.insert(at, FRAGMENTS[rng.below(FRAGMENTS.len())]),
+                _ => {
+                    let other = rng.below(words.len());
+                    words.swap(at, other);
+                }
+            }
+        }
+        run(&words.join(" "));
+    }
+}
+
+// deep recursion in several shapes; each one recurses until the interpreter stops it
+const RECURSIVE: &[&str] = &[
+    "fun f(n) { return f(n + 1); } f(0);",
+    "fun f(n) { { { { { { { { while (true) { return f(n + 1); } } } } } } } } } f(0);",
+    "fun f(n) { for (var i = 0; i < 1; i = i + 1) { if (true) { { var g = () => f(n + 1); return g(); } } } } f(0);",
+    "class A { init() { A(); } } A();",
+    "class A { toString() { return \"${this}\"; } } print A();",
+    "fun f(n) { return [f(n + 1)]; } f(0);",
+];
+
+#[test]
+fn deep_recursion_on_a_default_thread_is_a_runtime_error() {
+    // with the call depth limit out of the way the stack guard alone must hold
+    for (source, max_call_depth) in RECURSIVE.iter().flat_map(|s| [(s, None), (s, Some(usize::MAX))]) {
+        // a plain spawned thread gets the platform's default 2MB stack
+        let outcome = std::thread::spawn(move || {
+            let mut lox = Lox::new();
+            lox.interpreter().set_output(io::sink());
+            if let Some(depth) = max_call_depth {
+                lox.interpreter().set_max_call_depth(depth);
+            }
+            let result = lox.run(source);
+            let messages: Vec<String> = lox.diagnostics().take().into_iter().map(|d| d.message).collect();
+            (matches!(result, Err(RunError::Runtime)), messages)
+        })
+        .join();
+        let (failed, messages) = outcome.unwrap_or_else(|_| panic!("interpreter panicked on:\n{source}"));
+        assert!(failed, "expected a runtime error from:\n{source}");
+        assert_eq!(messages, ["Stack overflow."], "from:\n{source}");
+    }
+}
+
+// sources nested far deeper than any limit, in each shape the grammar can nest
+fn deeply_nested(n: usize) -> Vec<String> {
+    vec![
+        format!("print {}1{};", "(".repeat(n), ")".repeat(n)),
+        format!("{}{}", "{".repeat(n), "}".repeat(n)),
+        "{".repeat(n),
+        format!("print {}1{};", "[".repeat(n), "]".repeat(n)),
+        format!("print {}{{}}{};", "{\"k\": ".repeat(n), "}".repeat(n)),
+        format!("print {}1;", "-".repeat(n)),
+        format!("print !{}true;", "!".repeat(n)),
+        format!("print 1{};", " + 1".repeat(n)),
+        format!("print true{};", " and true".repeat(n)),
+        format!("{}print 1;", "if (true) ".repeat(n)),
+        format!("{}print 1;", "while (false) ".repeat(n)),
+        format!("{}{}", "fun f() { ".repeat(n), "}".repeat(n)),
+        format!("var a; {}1;", "a = ".repeat(n)),
+        format!("var a; print a{};", ".b".repeat(n)),
+        format!("fun f() {{ return f; }} print f{};", "()".repeat(n)),
+        format!("var a = [0]; print a{};", "[0]".repeat(n)),
+        format!("print {}1;", "() => ".repeat(n)),
+        format!("print {}1{};", "\"${".repeat(n), "}\"".repeat(n)),
+    ]
+}
+
+#[test]
+fn deep_nesting_on_a_default_thread_is_a_compile_error() {
+    for source in deeply_nested(20_000) {
+        // a plain spawned thread gets the platform's default 2MB stack
+        let outcome = std::thread::spawn({
+            let source = source.clone();
+            move || {
+                let mut lox = Lox::new();
+                lox.interpreter().set_output(io::sink());
+                let result = lox.run(&source);
+                let messages: Vec<String> = lox.diagnostics().take().into_iter().map(|d| d.message).collect();
+                (matches!(result, Err(RunError::Compile)), messages)
+            }
+        })
+        .join();
+        let shown = &source[..source.len().min(60)];
+        let (failed, messages) = outcome.unwrap_or_else(|_| panic!("interpreter panicked on:\n{shown}..."));
+        assert!(failed, "expected a compile error from:\n{shown}...");
+        assert!(messages.iter().any(|m| m == "Too much nesting."), "{messages:?} from:\n{shown}...");
+    }
+}
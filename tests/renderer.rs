@@ -0,0 +1,92 @@
+use lox_ast::{ColorMode, Lox, Renderer};
+
+// the first diagnostic a run reports, as the command line client prints it
+fn render(source: &str) -> String {
+    let mut lox = Lox::new();
+    lox.interpreter().set_output(std::io::sink());
+    let _ = lox.run(source);
+    let diagnostics = lox.diagnostics().take();
+    Renderer::new("test.lox", source, ColorMode::Plain).render(&diagnostics[0])
+}
+
+#[test]
+fn a_span_on_one_line_is_underlined() {
+    let expected = "\
+error[L0303]: Undefined variable 'total'.
+ --> test.lox:2:11
+  |
+2 | print a + total;
+  |           ^^^^^
+";
+    assert_eq!(render("var a = 1;\nprint a + total;\n"), expected);
+}
+
+#[test]
+fn labels_on_earlier_lines_are_shown_above() {
+    let expected = "\
+error[L0208]: Already a variable with this name in this scope.
+ --> test.lox:3:7
+  |
+2 |   var a = 1;
+  |       - 'a' first declared here
+3 |   var a = 2;
+  |       ^
+  = help: rename one of the declarations, or assign to the existing variable
+";
+    assert_eq!(render("{\n  var a = 1;\n  var a = 2;\n  print a;\n}\n"), expected);
+}
+
+#[test]
+fn a_span_over_several_lines_is_underlined_to_the_end_of_its_first_line() {
+    let expected = "\
+warning[L0404]: Unreachable code.
+ --> test.lox:3:3
+  |
+2 |   return;
+  |   ------- any code following this statement is unreachable
+3 |   { print 1;
+  |   ^^^^^^^^^^
+";
+    assert_eq!(render("fun f() {\n  return;\n  { print 1;\n    print 2; }\n}\nf();\n"), expected);
+}
+
+#[test]
+fn tabs_are_expanded_in_the_line_and_the_underline() {
+    let expected = "\
+error[L0303]: Undefined variable 'nope'.
+ --> test.lox:2:8
+  |
+2 |     print nope;
+  |           ^^^^
+  = backtrace:
+      at f (line 2)
+      at <script> (line 4)
+";
+    assert_eq!(render("fun f() {\n\tprint nope;\n}\nf();\n"), expected);
+}
+
+#[test]
+fn long_backtraces_keep_the_innermost_and_outermost_frames() {
+    let expected = "\
+error[L0303]: Undefined variable 'nope'.
+ --> test.lox:2:22
+  |
+2 |   if (n == 0) return nope;
+  |                      ^^^^
+  = backtrace:
+      at f (line 2)
+      at f (line 3)
+      at f (line 3)
+      at f (line 3)
+      at f (line 3)
+      at f (line 3)
+      at f (line 3)
+      at f (line 3)
+      at f (line 3)
+      at f (line 3)
+      ... 10 frames omitted
+      at f (line 3)
+      at <script> (line 5)
+";
+    assert_eq!(render("fun f(n) {\n  if (n == 0) return nope;\n  return f(n - 1);\n}\nf(20);\n"), expected);
+}
@@ -0,0 +1,105 @@
+use lox_ast::Lox;
+
+fn diagnostics(source: &str) -> Vec<(Option<&'static str>, String)> {
+    let mut lox = Lox::new();
+    lox.interpreter().set_output(std::io::sink());
+    let _ = lox.run(source);
+    lox.diagnostics().take().into_iter().map(|d| (d.code, d.message)).collect()
+}
+
+#[test]
+fn parse_errors_hide_unused_and_unreachable_warnings() {
+    // the broken statement may have been the one using 'a' and 'b'
+    let found = diagnostics("fun f(a) { var b = 1; print a + b +; return; print 2; }");
+    assert_eq!(found, [(Some("L0101"), "Expect expression.".to_string())]);
+}
+
+#[test]
+fn a_local_function_that_only_calls_itself_is_unused() {
+    let found = diagnostics("fun outer() { fun helper(n) { if (n > 0) helper(n - 1); } return 1; } outer();");
+    assert_eq!(found, [(Some("L0403"), "Unused local function 'helper'.".to_string())]);
+
+    let found = diagnostics("fun outer() { fun helper(n) { if (n > 0) helper(n - 1); } helper(2); } outer();");
+    assert_eq!(found, []);
+}
+
+// the source each warning with the given code points at
+fn flagged(code: &str, source: &str) -> Vec<String> {
+    let mut lox = Lox::new();
+    lox.interpreter().set_output(std::io::sink());
+    let _ = lox.run(source);
+    lox.diagnostics()
+        .take()
+        .into_iter()
+        .filter(|d| d.code == Some(code))
+        .filter_map(|d| d.span)
+        .map(|span| source[span.start..span.end].to_string())
+        .collect()
+}
+
+#[test]
+fn code_after_a_jump_is_unreachable() {
+    let found = diagnostics("fun f() { return 1; print 2; print 3; } f();");
+    assert_eq!(found, [(Some("L0404"), "Unreachable code.".to_string())]);
+    assert_eq!(flagged("L0404", "fun f() { return 1; print 2; print 3; } f();"), ["print 2;"]);
+    assert_eq!(flagged("L0404", "while (true) { break; print 1; }"), ["print 1;"]);
+    assert_eq!(flagged("L0404", "for (var i = 0; i < 2; i = i + 1) { continue; { print i; } }"), ["{ print i; }"]);
+    // only the code after the jump in the same block
+    assert_eq!(flagged("L0404", "fun f(a) { if (a) return 1; print 2; } f(true);"), Vec::<String>::new());
+}
+
+#[test]
+fn declarations_shadowing_outer_ones_are_flagged() {
+    let found = diagnostics("var a = 1; { var a = 2; print a; }");
+    assert_eq!(found, [(Some("L0405"), "Declaration shadows global 'a'.".to_string())]);
+
+    let found = diagnostics("fun f() { var b = 1; { var b = 2; print b; } print b; } f();");
+    assert_eq!(found, [(Some("L0405"), "Declaration shadows 'b' from an enclosing scope.".to_string())]);
+
+    // parameters and names starting with an underscore may shadow
+    assert_eq!(diagnostics("var a = 1; fun f(a) { return a; } print f(2);"), []);
+    assert_eq!(diagnostics("var _a = 1; { var _a = 2; print _a; }"), []);
+}
+
+#[test]
+fn stores_that_are_never_read_are_flagged() {
+    let found = diagnostics("fun f() { var a = 1; print a; a = 2; } f();");
+    assert_eq!(found, [(Some("L0406"), "Value assigned to 'a' is never read.".to_string())]);
+    assert_eq!(flagged("L0406", "fun f() { var a = 1; print a; a = 2; } f();"), ["a = 2"]);
+}
+
+#[test]
+fn stores_overwritten_before_being_read_are_flagged() {
+    let source = "fun f() { var a = 1; a = 2; a = 3; a = 4; print a; } f();";
+    assert_eq!(flagged("L0406", source), ["a = 2", "a = 3"]);
+
+    let source = "fun f(c) { var a = 1; if (c) { a = 2; a = 3; } else a = 4; print a; } f(true);";
+    assert_eq!(flagged("L0406", source), ["a = 2"]);
+
+    // the first store is read whenever the branch isn't taken
+    let source = "fun f(c) { var a = 1; a = 2; if (c) a = 3; print a; } f(true);";
+    assert_eq!(flagged("L0406", source), Vec::<String>::new());
+    let source = "fun f(c) { var a = 1; a = 2; c and (a = 3); print a; } f(true);";
+    assert_eq!(flagged("L0406", source), Vec::<String>::new());
+}
+
+#[test]
+fn stores_read_by_a_later_loop_iteration_are_not_flagged() {
+    let source = "fun f() { var a = 0; var i = 0; while (i < 3) { print a; a = i; i = i + 1; } } f();";
+    assert_eq!(flagged("L0406", source), Vec::<String>::new());
+
+    // a store that leaves the loop through break may be read after it
+    let source = "fun f(c) { var a = 0; while (true) { a = 1; if (c) break; a = 2; print a; } print a; } f(true);";
+    assert_eq!(flagged("L0406", source), Vec::<String>::new());
+
+    let source = "fun f() { var a = 0; print a; for (var i = 0; i < 3; i = i + 1) { a = i; } } f();";
+    assert_eq!(flagged("L0406", source), ["a = i"]);
+}
+
+#[test]
+fn stores_to_captured_variables_are_not_flagged() {
+    let source = "fun f() { var a = 0; fun show() { print a; } a = 1; show(); a = 2; a = 3; return show; } f()();";
+    assert_eq!(flagged("L0406", source), Vec::<String>::new());
+    let source = "fun f() { var a = 0; fun set() { a = 1; } set(); print a; } f();";
+    assert_eq!(flagged("L0406", source), Vec::<String>::new());
+}